@@ -0,0 +1,99 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    ops::Range,
+    pin::Pin,
+    task::{ready, Poll},
+};
+
+use super::StreamBroadcast;
+
+/// Fetches items from an external store, which a [BackfillBroadcast] would skip otherwise
+pub trait Backfill<I> {
+    type Fetch: Future<Output = Vec<I>>;
+
+    /// Fetches the items at the positions `range`. Items, which aren't returned, are reported as skipped.
+    fn fetch(&mut self, range: Range<u64>) -> Self::Fetch;
+}
+
+/// Created by [with_backfill](crate::StreamBroadcast::with_backfill)
+#[pin_project]
+pub struct BackfillBroadcast<T: Stream, B: Backfill<T::Item>> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    backfill: B,
+    fetch: Option<Pin<Box<B::Fetch>>>,
+    fetched: VecDeque<T::Item>,
+    /// Item received after the skipped range, with the number of items missing in the fetched ones
+    next: Option<(u64, T::Item)>,
+}
+
+impl<T: Stream, B: Backfill<T::Item>> BackfillBroadcast<T, B> {
+    pub(crate) fn new(inner: StreamBroadcast<T>, backfill: B) -> Self {
+        Self {
+            inner: inner.per_item(),
+            backfill,
+            fetch: None,
+            fetched: Default::default(),
+            next: None,
+        }
+    }
+
+    /// Returns the underlying broadcast. Items, which are fetched or not received yet, are lost.
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream, B: Backfill<T::Item>> Stream for BackfillBroadcast<T, B>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if let Some(fetch) = this.fetch {
+                let fetched = ready!(fetch.as_mut().poll(cx));
+                *this.fetch = None;
+                if let Some((missing, _)) = this.next {
+                    *missing = missing.saturating_sub(fetched.len() as u64);
+                }
+                this.fetched.extend(fetched);
+            }
+            if let Some(x) = this.fetched.pop_front() {
+                return Poll::Ready(Some((0, x)));
+            }
+            if let Some(next) = this.next.take() {
+                return Poll::Ready(Some(next));
+            }
+            let pos = this.inner.position();
+            match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some((0, x)) => return Poll::Ready(Some((0, x))),
+                Some((skipped, x)) => {
+                    *this.fetch = Some(Box::pin(this.backfill.fetch(pos..pos + skipped)));
+                    *this.next = Some((skipped, x));
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T: Stream, B: Backfill<T::Item>> FusedStream for BackfillBroadcast<T, B>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.fetch.is_none()
+            && self.fetched.is_empty()
+            && self.next.is_none()
+            && self.inner.is_terminated()
+    }
+}
@@ -0,0 +1,690 @@
+use futures::stream::Stream;
+
+use super::{
+    repeat_filter, BroadcastMetrics, BroadcastStorage, Checkpoints, ItemPool, RingStorage, Storage,
+    StreamBroadcast, StreamBroadcastState, SubscriberId,
+};
+
+/// What happens if the underlying stream produces an item, while a subscriber didn't receive the oldest buffered item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LagPolicy {
+    /// The oldest item is overwritten and slow subscribers skip it
+    #[default]
+    DropOldest,
+    /// The underlying stream isn't polled until all [StreamBroadcast]s received the oldest item
+    Backpressure,
+    /// Like [Backpressure](Self::Backpressure), but only for the given duration. Afterwards the oldest items are
+    /// overwritten like with [DropOldest](Self::DropOldest), until all [StreamBroadcast]s caught up again.
+    ///
+    /// This avoids blocking forever on a consumer, which stopped polling.
+    #[cfg(feature = "tokio")]
+    BackpressureFor(std::time::Duration),
+}
+
+impl LagPolicy {
+    /// Whether the underlying stream might wait for slow subscribers
+    pub(crate) fn blocks(self) -> bool {
+        self != LagPolicy::DropOldest
+    }
+}
+
+/// Position at which clones of a [StreamBroadcast] start reading
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StartPos {
+    /// Clones only receive items, which are produced after they were created
+    #[default]
+    Latest,
+    /// Clones receive all buffered items first
+    Oldest,
+}
+
+/// Which subscribers receive an item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Delivery {
+    /// Every [StreamBroadcast] receives every item
+    #[default]
+    Broadcast,
+    /// Each item is received by the first [StreamBroadcast] reading it
+    Distribute,
+    /// Item `n` is received by the `n % k`th of `k` [StreamBroadcast]s, ordered by creation
+    RoundRobin,
+}
+
+/// Which subscribers receive the errors of an underlying stream of [Result]s, set with
+/// [error_delivery](BroadcastBuilder::error_delivery)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorDelivery {
+    /// Errors are buffered like any other item, so every [StreamBroadcast] receives them
+    #[default]
+    Broadcast,
+    /// An error is only received by the [StreamBroadcast], whose poll produced it. It isn't buffered and doesn't
+    /// get a position, so the others neither receive it nor report it as skipped.
+    Poller,
+}
+
+/// How broadcasts continue after a panic poisoned their shared state, e.g. in the [Clone] implementation of an item
+///
+/// Set with [BroadcastBuilder::poison_policy] or [KeyedStreamBroadcast::poison_policy](crate::KeyedStreamBroadcast::poison_policy).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PoisonPolicy {
+    /// All further accesses panic
+    #[default]
+    Propagate,
+    /// Buffered items are dropped and all broadcasts terminate
+    Terminate,
+    /// The poison is cleared and the broadcasts continue with the state left by the panic
+    Recover,
+}
+
+/// Configures a [StreamBroadcast], created by [broadcast_builder](crate::StreamBroadcastExt::broadcast_builder)
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use futures::StreamExt;
+/// use stream_broadcast::{LagPolicy, StartPos, StreamBroadcastExt};
+///
+/// let mut broadcast = futures::stream::iter(0..5)
+///     .fuse()
+///     .broadcast_builder()
+///     .capacity(2)
+///     .lag_policy(LagPolicy::DropOldest)
+///     .start_at(StartPos::Oldest)
+///     .build();
+/// (&mut broadcast).take(3).count().await;
+/// assert_eq!(vec![(0, 1), (0, 2), (0, 3), (0, 4)], broadcast.clone().collect::<Vec<_>>().await);
+/// # }
+/// ```
+pub struct BroadcastBuilder<T: Stream> {
+    state: StreamBroadcastState<T>,
+}
+
+/// Capacity if [capacity](BroadcastBuilder::capacity) isn't called
+const DEFAULT_CAPACITY: usize = 16;
+
+impl<T: Stream> BroadcastBuilder<T>
+where
+    T::Item: Clone,
+{
+    pub(crate) fn new(stream: T) -> Self {
+        Self {
+            state: StreamBroadcastState::new(stream, DEFAULT_CAPACITY),
+        }
+    }
+
+    /// Maximum number of buffered items. Defaults to 16.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.state.cache.resize(capacity, 0..0);
+        self
+    }
+
+    /// Buffers the items in `storage` instead of a [RingStorage](crate::RingStorage)
+    ///
+    /// This allows e.g. instrumented or memory-mapped stores without changing how items are broadcasted.
+    /// The capacity of `storage` is used, unless [capacity](Self::capacity) is called afterwards.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{BroadcastStorage, RingStorage, StreamBroadcastExt};
+    /// use std::ops::Range;
+    ///
+    /// /// Counts the items pushed into a [RingStorage]
+    /// struct Counting(RingStorage<i32>, usize);
+    ///
+    /// impl BroadcastStorage<i32> for Counting {
+    ///     fn capacity(&self) -> usize {
+    ///         self.0.capacity()
+    ///     }
+    ///     fn push(&mut self, pos: u64, slot: Option<i32>) {
+    ///         self.1 += 1;
+    ///         self.0.push(pos, slot)
+    ///     }
+    ///     fn get(&self, pos: u64) -> Option<&i32> {
+    ///         self.0.get(pos)
+    ///     }
+    ///     fn evict(&mut self, pos: u64) -> Option<i32> {
+    ///         self.0.evict(pos)
+    ///     }
+    ///     fn resize(&mut self, capacity: usize, stored: Range<u64>) {
+    ///         self.0.resize(capacity, stored)
+    ///     }
+    /// }
+    ///
+    /// let broadcast = futures::stream::iter(0..5)
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .storage(Counting(RingStorage::new(2), 0))
+    ///     .build();
+    /// assert_eq!(2, broadcast.capacity());
+    /// assert_eq!(5, broadcast.count().await);
+    /// # }
+    /// ```
+    pub fn storage(mut self, storage: impl BroadcastStorage<T::Item> + Send + 'static) -> Self {
+        self.state.cache = Storage::Custom(Box::new(storage));
+        self
+    }
+
+    /// Clones the items after the shared state is unlocked, so an expensive [Clone] doesn't block other broadcasts
+    ///
+    /// The buffered items are wrapped in an [Arc](std::sync::Arc) and a broadcast only takes another reference
+    /// while holding the lock. This costs an allocation per item and requires the items to be [Sync], so it only
+    /// pays off if cloning an item is more expensive than that. Replaces a previously set [storage](Self::storage).
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(["a".repeat(1000), "b".repeat(1000)])
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .clone_unlocked()
+    ///     .build();
+    /// let (a, b) = futures::join!(broadcast.clone().count(), broadcast.count());
+    /// assert_eq!((2, 2), (a, b));
+    /// # }
+    /// ```
+    pub fn clone_unlocked(mut self) -> Self
+    where
+        T::Item: Send + Sync + 'static,
+    {
+        let capacity = self.state.cache.capacity();
+        self.state.cache = Storage::Shared(Box::new(RingStorage::new(capacity)));
+        self
+    }
+
+    /// Moves the items out of the cache instead of cloning them, while a single [StreamBroadcast] is left
+    ///
+    /// This only happens as long as no clone or [WeakStreamBroadcast](crate::WeakStreamBroadcast) exists, which
+    /// could receive the item. Otherwise, the items are cloned as usual. The moved items are no longer buffered, so
+    /// [with_latest](crate::StreamBroadcast::with_latest) and clones created later don't see them anymore.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..3).fuse().broadcast_builder().move_to_sole_subscriber().build();
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// assert_eq!(0, broadcast.buffered_len());
+    /// let clone = broadcast.clone();
+    /// assert_eq!(Some((0, 1)), broadcast.next().await);
+    /// assert_eq!(1, broadcast.buffered_len());
+    /// # drop(clone);
+    /// # }
+    /// ```
+    pub fn move_to_sole_subscriber(mut self) -> Self {
+        self.state.move_sole = true;
+        self
+    }
+
+    pub fn lag_policy(mut self, policy: LagPolicy) -> Self {
+        self.state.lag_policy = policy;
+        self
+    }
+
+    /// See [distribute](crate::StreamBroadcastExt::distribute) and [round_robin](crate::StreamBroadcastExt::round_robin)
+    pub fn delivery(mut self, delivery: Delivery) -> Self {
+        self.state.delivery = delivery;
+        self
+    }
+
+    pub fn start_at(mut self, start: StartPos) -> Self {
+        self.state.start = start;
+        self
+    }
+
+    /// Keeps every item produced by the underlying stream, so late subscribers can read the full history
+    ///
+    /// The cache grows without limit and [capacity](Self::capacity) is just the initial size.
+    /// Combine with [StartPos::Oldest] or [resubscribe_from_start](crate::StreamBroadcast::resubscribe_from_start).
+    pub fn retain_all(mut self) -> Self {
+        self.state.retain_all = true;
+        self
+    }
+
+    /// Treats buffered items as evicted once they are older than `ttl`, even if the cache isn't full yet
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{StartPos, StreamBroadcastExt};
+    /// use std::time::Duration;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..3)
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .ttl(Duration::from_millis(10))
+    ///     .start_at(StartPos::Oldest)
+    ///     .build();
+    /// broadcast.next().await;
+    /// assert_eq!(Some(0), broadcast.with_latest(|x| x.copied()));
+    /// tokio::time::sleep(Duration::from_millis(20)).await;
+    /// assert_eq!(None, broadcast.with_latest(|x| x.copied()));
+    /// # }
+    /// ```
+    pub fn ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.state.ttl = Some(ttl);
+        self
+    }
+
+    /// See [broadcast_weighted](crate::StreamBroadcastExt::broadcast_weighted)
+    ///
+    /// The cache grows beyond [capacity](Self::capacity) as long as the buffered items stay within `max_weight`.
+    pub fn max_weight(
+        mut self,
+        max_weight: usize,
+        weigh: impl Fn(&T::Item) -> usize + Send + 'static,
+    ) -> Self {
+        self.state.weigher = Some(super::Weigher {
+            max: max_weight,
+            weigh: Box::new(weigh),
+        });
+        self
+    }
+
+    /// See [broadcast_inspect](crate::StreamBroadcastExt::broadcast_inspect) and [tap](crate::StreamBroadcast::tap)
+    pub fn inspect(mut self, f: impl FnMut(&T::Item) + Send + 'static) -> Self {
+        self.state.taps.push(Box::new(f));
+        self
+    }
+
+    /// Calls `f` with every item evicted from the cache, which isn't passed to the [overflow_sink](Self::overflow_sink)
+    ///
+    /// Items, which a broadcast is still cloning after [clone_unlocked](Self::clone_unlocked), are dropped instead.
+    /// Use [recycle](Self::recycle) to reuse the items.
+    pub fn on_evict(mut self, f: impl FnMut(T::Item) + Send + 'static) -> Self {
+        self.state.on_evict = Some(Box::new(f));
+        self
+    }
+
+    /// Puts the evicted items into `pool`, so the underlying stream can reuse them instead of allocating new ones
+    ///
+    /// See [on_evict](Self::on_evict).
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{ItemPool, StreamBroadcastExt};
+    ///
+    /// let pool = ItemPool::new(4);
+    /// let frames = futures::stream::iter(0..10u8).map({
+    ///     let pool = pool.clone();
+    ///     move |i| {
+    ///         let mut frame: Vec<u8> = pool.take().unwrap_or_else(|| Vec::with_capacity(1024));
+    ///         frame.clear();
+    ///         frame.push(i);
+    ///         frame
+    ///     }
+    /// });
+    /// let broadcast = frames.fuse().broadcast_builder().capacity(2).recycle(&pool).build();
+    /// assert_eq!(10, broadcast.count().await);
+    /// // Each frame reused the one evicted before, so only 3 frames were allocated
+    /// assert_eq!(1, pool.len());
+    /// # }
+    /// ```
+    pub fn recycle(self, pool: &ItemPool<T::Item>) -> Self
+    where
+        T::Item: Send + 'static,
+    {
+        let pool = pool.clone();
+        self.on_evict(move |item| pool.put(item))
+    }
+
+    /// See [set_overflow_sink](crate::StreamBroadcast::set_overflow_sink)
+    pub fn overflow_sink(mut self, f: impl FnMut(T::Item) + Send + 'static) -> Self {
+        self.state.overflow = Some(Box::new(f));
+        self
+    }
+
+    /// Defaults to [PoisonPolicy::Propagate]
+    pub fn poison_policy(mut self, policy: PoisonPolicy) -> Self {
+        self.state.poison_policy = policy;
+        self
+    }
+
+    /// See [set_on_lag](crate::StreamBroadcast::set_on_lag)
+    pub fn on_lag(mut self, f: impl FnMut(SubscriberId, u64) + Send + 'static) -> Self {
+        self.state.on_lag = Some(Box::new(f));
+        self
+    }
+
+    /// Reports the counters of the broadcast to `metrics`
+    pub fn metrics(mut self, metrics: impl BroadcastMetrics + 'static) -> Self {
+        self.state.metrics = Some(Box::new(metrics));
+        self
+    }
+
+    /// Appends items evicted from the cache to `file`, so lagging broadcasts read them from disk instead of skipping
+    ///
+    /// This allows replay windows, which don't fit into memory. Lagging broadcasts read the spilled items before
+    /// they continue with the cache. The file is reused once no broadcast needs the spilled items anymore.
+    /// Only used with [Delivery::Broadcast]. Items, which can't be written or read, are skipped.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{SpillCodec, StreamBroadcastExt};
+    ///
+    /// struct Codec;
+    /// impl SpillCodec<u32> for Codec {
+    ///     fn encode(&mut self, item: &u32, buf: &mut Vec<u8>) {
+    ///         buf.extend(item.to_le_bytes());
+    ///     }
+    ///     fn decode(&mut self, bytes: &[u8]) -> std::io::Result<u32> {
+    ///         Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    ///     }
+    /// }
+    ///
+    /// let path = std::env::temp_dir().join("stream-broadcast-spill-doc");
+    /// let file = std::fs::File::options()
+    ///     .read(true)
+    ///     .write(true)
+    ///     .create(true)
+    ///     .truncate(true)
+    ///     .open(&path)
+    ///     .unwrap();
+    /// let mut broadcast = futures::stream::iter(0..5)
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .capacity(2)
+    ///     .spill(file, Codec)
+    ///     .build();
+    /// let lagging = broadcast.clone();
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// assert_eq!(vec![0, 1, 2, 3, 4], lagging.map(|(_, x)| x).collect::<Vec<_>>().await);
+    /// # std::fs::remove_file(path).unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "spill")]
+    pub fn spill(
+        mut self,
+        file: std::fs::File,
+        codec: impl crate::SpillCodec<T::Item> + 'static,
+    ) -> Self {
+        self.state.spill = Some(crate::spill::Spill::new(file, Box::new(codec)));
+        self
+    }
+
+    /// Records every item and the broadcasts, which received it, in `recorder`, see [Recorder](crate::Recorder)
+    #[cfg(feature = "recorder")]
+    pub fn record(mut self, recorder: &crate::Recorder<T::Item>) -> Self {
+        self.state.recorder = Some(recorder.clone());
+        self
+    }
+
+    /// Stops polling the underlying stream once `token` is cancelled
+    ///
+    /// If `drain` is set, the broadcasts receive the buffered items before they terminate like after
+    /// [close](crate::StreamBroadcast::close). Otherwise, the buffered items are dropped and all broadcasts terminate
+    /// right away. The token is only observed while a broadcast waits for the underlying stream.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use tokio_util::sync::CancellationToken;
+    ///
+    /// let token = CancellationToken::new();
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let mut broadcast = rx.broadcast_builder().cancellation(token.clone(), true).build();
+    /// let lagging = broadcast.clone();
+    /// tx.unbounded_send(1).unwrap();
+    /// assert_eq!(Some((0, 1)), broadcast.next().await);
+    /// token.cancel();
+    /// assert_eq!(None, broadcast.next().await);
+    /// assert_eq!(vec![(0, 1)], lagging.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio-util")]
+    pub fn cancellation(mut self, token: tokio_util::sync::CancellationToken, drain: bool) -> Self {
+        self.state.cancellation = Some(crate::cancel::Cancellation::new(token, drain));
+        self
+    }
+
+    /// Pushes an item created by `factory` to all broadcasts, whenever the underlying stream produced no item
+    /// for `interval`
+    ///
+    /// Heartbeats are buffered like the items of the underlying stream, e.g. to keep downstream protocols alive.
+    /// The interval only elapses while a broadcast waits for the underlying stream.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let mut broadcast = rx
+    ///     .broadcast_builder()
+    ///     .heartbeat(Duration::from_millis(10), || None)
+    ///     .build();
+    /// tx.unbounded_send(Some(1)).unwrap();
+    /// assert_eq!(Some((0, Some(1))), broadcast.next().await);
+    /// assert_eq!(Some((0, None)), broadcast.next().await);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn heartbeat(
+        mut self,
+        interval: std::time::Duration,
+        factory: impl FnMut() -> T::Item + Send + 'static,
+    ) -> Self {
+        self.state.heartbeat = Some(crate::heartbeat::Heartbeat::new(interval, factory));
+        self
+    }
+
+    /// Drops items of the underlying stream, which are equal to the previous one, before they are cached
+    ///
+    /// Duplicates don't get a position, so they are neither received nor reported as skipped.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter([1, 1, 2, 2, 2, 1])
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .dedup()
+    ///     .build();
+    /// assert_eq!(vec![(0, 1), (0, 2), (0, 1)], broadcast.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn dedup(mut self) -> Self
+    where
+        T::Item: PartialEq + Send + 'static,
+    {
+        self.state.dedup = Some(repeat_filter());
+        self
+    }
+
+    /// Every broadcast receives `seed` before the items of the underlying stream, e.g. the current configuration
+    ///
+    /// The seed is stored once and doesn't occupy the capacity of the cache. It is received with an offset of 0.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(1..3).fuse().broadcast_builder().seed(0).build();
+    /// assert_eq!(vec![(0, 0), (0, 1), (0, 2)], (&mut broadcast).collect::<Vec<_>>().await);
+    /// assert_eq!(vec![(0, 0)], broadcast.clone().collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn seed(mut self, seed: T::Item) -> Self {
+        self.state.seed = Some(seed);
+        self
+    }
+
+    /// Keeps up to `limit` evicted items, for which `select` returns true, so badly lagging broadcasts
+    /// resynchronize from them instead of skipping to the oldest buffered item
+    ///
+    /// `select` is called with the position and the item, when it's evicted from the cache. A lagging broadcast
+    /// receives the checkpoints after its position, before it continues with the cache. The skipped items in between
+    /// are reported as usual. Only used with [Delivery::Broadcast].
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10)
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .capacity(2)
+    ///     .checkpoints(8, |pos, _| pos % 3 == 0)
+    ///     .build();
+    /// let lagging = broadcast.clone();
+    /// assert_eq!(10, (&mut broadcast).count().await);
+    /// assert_eq!(
+    ///     vec![(0, 0), (2, 3), (2, 6), (1, 8), (0, 9)],
+    ///     lagging.collect::<Vec<_>>().await
+    /// );
+    /// # }
+    /// ```
+    pub fn checkpoints(
+        mut self,
+        limit: usize,
+        select: impl FnMut(u64, &T::Item) -> bool + Send + 'static,
+    ) -> Self {
+        self.state.checkpoints = Some(Checkpoints {
+            select: Box::new(select),
+            items: Default::default(),
+            limit,
+        });
+        self
+    }
+
+    /// Rotates polling the underlying stream among the waiting broadcasts, instead of letting the first one
+    /// polling an exhausted cache pay for it every time
+    ///
+    /// A broadcast, which polled the underlying stream last time, wakes the next waiting broadcast to poll it instead.
+    /// This distributes the cost of an expensive stream at the price of additional wakeups. If the woken broadcast
+    /// doesn't poll anymore, e.g. because its `next()` future was dropped, the previous one takes the role back.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..3).fuse().broadcast_builder().rotate_driver().build();
+    /// let (a, b) = futures::join!(broadcast.clone().count(), broadcast.count());
+    /// assert_eq!((3, 3), (a, b));
+    /// # }
+    /// ```
+    pub fn rotate_driver(mut self) -> Self {
+        self.state.rotate_driver = true;
+        self
+    }
+
+    /// Priority of all broadcasts, until they call [set_priority](crate::StreamBroadcast::set_priority). Defaults to 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.state.default_priority = priority;
+        self
+    }
+
+    /// Lets [pump](crate::StreamBroadcast::pump) and [broadcast_driven](crate::StreamBroadcastExt::broadcast_driven)
+    /// keep the cache filled up to `capacity` items ahead of the fastest broadcast, instead of polling the underlying
+    /// stream as fast as possible
+    ///
+    /// Subscribers read prefetched items without waiting for the underlying stream, while the items they didn't read
+    /// yet are not overwritten by the driver.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..10).fuse().broadcast_builder().capacity(3).prefetch().build();
+    /// let mut pump = std::pin::pin!(broadcast.pump());
+    /// assert!(futures::poll!(&mut pump).is_pending());
+    /// assert_eq!(3, broadcast.lag());
+    /// assert_eq!(((), 10), futures::join!(pump, broadcast.count()));
+    /// # }
+    /// ```
+    pub fn prefetch(mut self) -> Self {
+        self.state.prefetch = true;
+        self
+    }
+
+    /// Doesn't poll the underlying stream until `n` broadcasts were created, e.g. because reading it is destructive
+    ///
+    /// Once reached, the underlying stream is polled even if broadcasts are dropped again.
+    /// See [wait_for_subscribers](crate::StreamBroadcast::wait_for_subscribers).
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..3).fuse().broadcast_builder().min_subscribers(2).build();
+    /// assert!(futures::poll!(broadcast.next()).is_pending());
+    /// let late = broadcast.clone();
+    /// assert_eq!(3, broadcast.count().await);
+    /// assert_eq!(3, late.count().await);
+    /// # }
+    /// ```
+    pub fn min_subscribers(mut self, n: usize) -> Self {
+        self.state.min_subscribers = n;
+        self
+    }
+
+    pub fn build(self) -> StreamBroadcast<T> {
+        StreamBroadcast::from_state(self.state)
+    }
+}
+
+impl<T, I, E> BroadcastBuilder<T>
+where
+    T: Stream<Item = Result<I, E>>,
+    T::Item: Clone,
+{
+    /// Selects which broadcasts receive the errors of the underlying stream
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{ErrorDelivery, StreamBroadcastExt};
+    ///
+    /// let mut broadcast = futures::stream::iter([Ok(1), Err("failed"), Ok(2)])
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .error_delivery(ErrorDelivery::Poller)
+    ///     .build();
+    /// let other = broadcast.clone();
+    /// assert_eq!(Some((0, Ok(1))), broadcast.next().await);
+    /// assert_eq!(Some((0, Err("failed"))), broadcast.next().await);
+    /// assert_eq!(vec![(0, Ok(1)), (0, Ok(2))], other.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn error_delivery(mut self, delivery: ErrorDelivery) -> Self {
+        self.state.is_private = match delivery {
+            ErrorDelivery::Broadcast => None,
+            ErrorDelivery::Poller => Some(Result::is_err),
+        };
+        self
+    }
+}
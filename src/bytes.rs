@@ -0,0 +1,382 @@
+//! Fan-out of byte streams to many writers, available with the `bytes` feature
+
+use ::bytes::{Buf, Bytes, BytesMut};
+use futures::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    ready,
+    stream::{FusedStream, Stream},
+    StreamExt,
+};
+use pin_project::pin_project;
+use std::{
+    io::ErrorKind,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use super::{BroadcastError, StreamBroadcast, StreamBroadcastExt};
+
+pub trait BytesBroadcastExt: Stream<Item = Bytes> + Sized {
+    /// Broadcasts chunks of bytes, buffering at most `size` chunks
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use bytes::Bytes;
+    /// use futures::StreamExt;
+    /// use stream_broadcast::BytesBroadcastExt;
+    ///
+    /// let chunks = [Bytes::from_static(b"Hello "), Bytes::from_static(b"World")];
+    /// let broadcast = futures::stream::iter(chunks).fuse().broadcast_bytes(16);
+    /// let mut writers = vec![Vec::new(), Vec::new()];
+    /// let copies = writers
+    ///     .iter_mut()
+    ///     .map(|w| broadcast.clone().copy_to(w))
+    ///     .collect::<Vec<_>>();
+    /// drop(broadcast);
+    /// for result in futures::future::join_all(copies).await {
+    ///     assert_eq!(11, result.unwrap());
+    /// }
+    /// assert_eq!(b"Hello World", &writers[1][..]);
+    /// # }
+    /// ```
+    fn broadcast_bytes(self, size: usize) -> BytesBroadcast<Self>;
+
+    /// Like [broadcast_bytes](Self::broadcast_bytes), but coalesces the chunks, which are ready at the same
+    /// time, into frames of about `frame_size` bytes and buffers at most `max_bytes`. A `frame_size` of 0 is
+    /// treated as 1, so every chunk is passed on separately.
+    ///
+    /// This avoids the overhead per item for streams of tiny chunks. [lag](BytesBroadcast::lag) and
+    /// [skipped](BytesBroadcast::skipped) count frames instead of chunks.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use bytes::Bytes;
+    /// use futures::StreamExt;
+    /// use stream_broadcast::BytesBroadcastExt;
+    ///
+    /// let chunks = (0..10).map(|_| Bytes::from_static(b"ab"));
+    /// let broadcast = futures::stream::iter(chunks).broadcast_bytes_coalesced(64, 8);
+    /// let frames = broadcast.collect::<Vec<_>>().await;
+    /// assert_eq!(vec![8, 8, 4], frames.iter().map(|f| f.len()).collect::<Vec<_>>());
+    /// # }
+    /// ```
+    fn broadcast_bytes_coalesced(
+        self,
+        max_bytes: usize,
+        frame_size: usize,
+    ) -> BytesBroadcast<CoalescedBytes<Self>>;
+}
+
+impl<T: Stream<Item = Bytes>> BytesBroadcastExt for T {
+    fn broadcast_bytes(self, size: usize) -> BytesBroadcast<Self> {
+        StreamBroadcast::new(self, size).into()
+    }
+
+    fn broadcast_bytes_coalesced(
+        self,
+        max_bytes: usize,
+        frame_size: usize,
+    ) -> BytesBroadcast<CoalescedBytes<Self>> {
+        let frames = CoalescedBytes {
+            inner: self,
+            frame_size: frame_size.max(1),
+            terminated: false,
+        };
+        frames
+            .broadcast_builder()
+            .capacity(1)
+            .max_weight(max_bytes, Bytes::len)
+            .build()
+            .into()
+    }
+}
+
+/// Underlying stream of [broadcast_bytes_coalesced](BytesBroadcastExt::broadcast_bytes_coalesced)
+#[pin_project]
+pub struct CoalescedBytes<T> {
+    #[pin]
+    inner: T,
+    frame_size: usize,
+    terminated: bool,
+}
+
+impl<T: Stream<Item = Bytes>> Stream for CoalescedBytes<T> {
+    type Item = Bytes;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        // A single chunk is passed on without copying it
+        let mut first: Option<Bytes> = None;
+        let mut frame = BytesMut::new();
+        while !*this.terminated {
+            let len = if frame.is_empty() {
+                first.as_ref().map_or(0, Bytes::len)
+            } else {
+                frame.len()
+            };
+            if len >= *this.frame_size {
+                break;
+            }
+            match this.inner.as_mut().poll_next(cx) {
+                Poll::Ready(Some(chunk)) => match &first {
+                    None => first = Some(chunk),
+                    Some(first) => {
+                        if frame.is_empty() {
+                            frame.extend_from_slice(first);
+                        }
+                        frame.extend_from_slice(&chunk);
+                    }
+                },
+                Poll::Ready(None) => *this.terminated = true,
+                Poll::Pending if first.is_none() => return Poll::Pending,
+                Poll::Pending => break,
+            }
+        }
+        if frame.is_empty() {
+            Poll::Ready(first)
+        } else {
+            Poll::Ready(Some(frame.freeze()))
+        }
+    }
+}
+
+impl<T: Stream<Item = Bytes>> FusedStream for CoalescedBytes<T> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// Broadcast of [Bytes], created by [broadcast_bytes](BytesBroadcastExt::broadcast_bytes)
+///
+/// Yields the chunks without the offset. Skipped chunks are counted in [skipped](Self::skipped) instead.
+#[pin_project]
+pub struct BytesBroadcast<T: Stream<Item = Bytes>> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    skipped: u64,
+}
+
+impl<T: Stream<Item = Bytes>> From<StreamBroadcast<T>> for BytesBroadcast<T> {
+    fn from(inner: StreamBroadcast<T>) -> Self {
+        Self {
+            inner: inner.per_item(),
+            skipped: 0,
+        }
+    }
+}
+
+impl<T: Stream<Item = Bytes>> Clone for BytesBroadcast<T> {
+    fn clone(&self) -> Self {
+        self.inner.clone().into()
+    }
+}
+
+impl<T: Stream<Item = Bytes>> BytesBroadcast<T> {
+    /// Number of chunks produced, which this broadcast didn't receive yet
+    pub fn lag(&self) -> u64 {
+        self.inner.lag()
+    }
+
+    /// Number of chunks skipped, because this broadcast fell behind by more than `size` chunks
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Writes all chunks to `writer` and returns the number of bytes written
+    ///
+    /// Skipping chunks would corrupt the written data, so this fails with [BroadcastError::Lagged]
+    /// (wrapped in an [std::io::Error]) as soon as the writer falls behind by more than `size` chunks.
+    pub async fn copy_to<W: AsyncWrite + Unpin>(self, mut writer: W) -> std::io::Result<u64> {
+        let mut inner = self.inner;
+        let mut written = 0;
+        while let Some((skipped, chunk)) = inner.next().await {
+            if skipped > 0 {
+                return Err(std::io::Error::other(BroadcastError::Lagged { skipped }));
+            }
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        writer.flush().await?;
+        Ok(written)
+    }
+}
+
+impl<T: Stream<Item = Bytes>> Stream for BytesBroadcast<T> {
+    type Item = Bytes;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.inner.poll_next(cx).map(|x| {
+            x.map(|(skipped, chunk)| {
+                *this.skipped += skipped;
+                chunk
+            })
+        })
+    }
+}
+
+impl<T: Stream<Item = Bytes>> FusedStream for BytesBroadcast<T> {
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+pub trait AsyncReadBroadcastExt: AsyncRead + Sized {
+    /// Reads chunks of at most `chunk_size` bytes and shares them between the clones of the returned
+    /// [AsyncRead], buffering at most `size` chunks
+    ///
+    /// The buffered chunks are the read-ahead window: The fastest clone reads ahead of the slowest one by at
+    /// most `size` chunks, before the slowest one fails with [BroadcastError::Lagged]. A `chunk_size` of 0 is
+    /// treated as 1, because reading into an empty buffer can't be told apart from the end of the reader.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::AsyncReadExt;
+    /// use stream_broadcast::AsyncReadBroadcastExt;
+    ///
+    /// let mut reader = futures::io::Cursor::new(b"Hello World").broadcast_read(4, 8);
+    /// let mut reader2 = reader.clone();
+    /// let (mut a, mut b) = (String::new(), String::new());
+    /// reader.read_to_string(&mut a).await.unwrap();
+    /// reader2.read_to_string(&mut b).await.unwrap();
+    /// assert_eq!(("Hello World", "Hello World"), (&a[..], &b[..]));
+    /// # }
+    /// ```
+    fn broadcast_read(self, chunk_size: usize, size: usize) -> ReadBroadcast<Self>;
+}
+
+impl<R: AsyncRead> AsyncReadBroadcastExt for R {
+    fn broadcast_read(self, chunk_size: usize, size: usize) -> ReadBroadcast<Self> {
+        let error = Arc::new(Mutex::new(None));
+        let chunks = ReaderChunks {
+            reader: self,
+            chunk_size: chunk_size.max(1),
+            buf: BytesMut::new(),
+            error: error.clone(),
+            terminated: false,
+        };
+        ReadBroadcast {
+            inner: StreamBroadcast::new(chunks, size),
+            chunk: Bytes::new(),
+            error,
+        }
+    }
+}
+
+/// Error of the underlying reader, which is reported to every clone of a [ReadBroadcast]
+type SharedError = Arc<Mutex<Option<(ErrorKind, String)>>>;
+
+/// Underlying stream of [broadcast_read](AsyncReadBroadcastExt::broadcast_read)
+#[pin_project]
+pub struct ReaderChunks<R> {
+    #[pin]
+    reader: R,
+    chunk_size: usize,
+    buf: BytesMut,
+    error: SharedError,
+    terminated: bool,
+}
+
+impl<R: AsyncRead> Stream for ReaderChunks<R> {
+    type Item = Bytes;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        while !*this.terminated {
+            this.buf.resize(*this.chunk_size, 0);
+            match ready!(this.reader.as_mut().poll_read(cx, this.buf)) {
+                Ok(0) => *this.terminated = true,
+                Ok(n) => {
+                    this.buf.truncate(n);
+                    return Poll::Ready(Some(this.buf.split().freeze()));
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    *this.error.lock().unwrap_or_else(|e| e.into_inner()) =
+                        Some((e.kind(), e.to_string()));
+                    *this.terminated = true;
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+impl<R: AsyncRead> FusedStream for ReaderChunks<R> {
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+/// [AsyncRead] created by [broadcast_read](AsyncReadBroadcastExt::broadcast_read)
+///
+/// Clones continue reading at the same byte as the original. Skipping chunks would corrupt the read data, so
+/// a clone, which falls behind by more than `size` chunks, fails with [BroadcastError::Lagged] (wrapped in an
+/// [std::io::Error]). An error of the underlying reader is reported to all clones after the preceding bytes.
+pub struct ReadBroadcast<R: AsyncRead> {
+    inner: StreamBroadcast<ReaderChunks<R>>,
+    /// Remainder of the chunk, which is partially read
+    chunk: Bytes,
+    error: SharedError,
+}
+
+impl<R: AsyncRead> Clone for ReadBroadcast<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            chunk: self.chunk.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+impl<R: AsyncRead> ReadBroadcast<R> {
+    /// Number of chunks read from the underlying reader, which this clone didn't receive yet
+    pub fn lag(&self) -> u64 {
+        self.inner.lag()
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for ReadBroadcast<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        while this.chunk.is_empty() && !buf.is_empty() {
+            match ready!(this.inner.poll_next_unpin(cx)) {
+                Some((0, chunk)) => this.chunk = chunk,
+                Some((skipped, _)) => {
+                    return Poll::Ready(Err(std::io::Error::other(BroadcastError::Lagged {
+                        skipped,
+                    })))
+                }
+                None => {
+                    let error = this.error.lock().unwrap_or_else(|e| e.into_inner());
+                    return Poll::Ready(match &*error {
+                        Some((kind, message)) => Err(std::io::Error::new(*kind, message.clone())),
+                        None => Ok(0),
+                    });
+                }
+            }
+        }
+        let len = buf.len().min(this.chunk.len());
+        buf[..len].copy_from_slice(&this.chunk[..len]);
+        this.chunk.advance(len);
+        Poll::Ready(Ok(len))
+    }
+}
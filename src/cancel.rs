@@ -0,0 +1,25 @@
+//! Cooperative shutdown with a [CancellationToken], available with the `tokio-util` feature
+
+use std::{future::Future, pin::Pin, task::Poll};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+
+/// Set by [BroadcastBuilder::cancellation](crate::BroadcastBuilder::cancellation)
+pub(crate) struct Cancellation {
+    cancelled: Pin<Box<WaitForCancellationFutureOwned>>,
+    /// Whether subscribers receive the buffered items before they terminate
+    pub(crate) drain: bool,
+}
+
+impl Cancellation {
+    pub(crate) fn new(token: CancellationToken, drain: bool) -> Self {
+        Self {
+            cancelled: Box::pin(token.cancelled_owned()),
+            drain,
+        }
+    }
+
+    /// Wakes `cx` when the token is cancelled
+    pub(crate) fn poll(&mut self, cx: &mut std::task::Context<'_>) -> Poll<()> {
+        self.cancelled.as_mut().poll(cx)
+    }
+}
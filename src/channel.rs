@@ -0,0 +1,160 @@
+use futures::{
+    stream::{FusedStream, Stream},
+    Sink,
+};
+use std::{pin::Pin, task::Poll};
+
+use super::{create_id, lock_state, try_lock_state, SharedState, StreamBroadcast};
+
+/// Receiving side of a [channel]
+pub type Receiver<T> = StreamBroadcast<ChannelSource<T>>;
+
+/// Creates a broadcast, whose items are pushed by a [Sender] instead of polled from a stream
+///
+/// The receivers terminate after all senders were dropped or closed and the buffered items were received.
+/// Sending never waits: receivers, which lag more than `capacity` items, skip the oldest ones like with
+/// [LagPolicy::DropOldest](crate::LagPolicy::DropOldest).
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use futures::{SinkExt, StreamExt};
+///
+/// let (mut sender, receiver) = stream_broadcast::channel(3);
+/// let receiver2 = receiver.clone();
+/// sender.send(1).await.unwrap();
+/// sender.push(2);
+/// drop(sender);
+/// assert_eq!(vec![(0, 1), (0, 2)], receiver.collect::<Vec<_>>().await);
+/// assert_eq!(vec![(0, 1), (0, 2)], receiver2.collect::<Vec<_>>().await);
+/// # }
+/// ```
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let source = ChannelSource {
+        senders: 1,
+        closed: false,
+        _item: std::marker::PhantomData,
+    };
+    let receiver = StreamBroadcast::new(source, capacity);
+    let sender = Sender {
+        id: create_id(),
+        state: receiver.state.clone(),
+    };
+    (sender, receiver)
+}
+
+/// Underlying stream of a [Receiver], which never yields items on its own
+pub struct ChannelSource<T> {
+    senders: usize,
+    closed: bool,
+    _item: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> Stream for ChannelSource<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        // Receivers are woken by the senders
+        if self.closed {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> FusedStream for ChannelSource<T> {
+    fn is_terminated(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Sending side of a [channel]
+pub struct Sender<T> {
+    id: u64,
+    state: SharedState<ChannelSource<T>>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// Pushes `item` to all receivers without waiting
+    ///
+    /// Items pushed after the channel was [closed](Self::close) or the receivers terminated are ignored.
+    pub fn push(&self, item: T) {
+        let mut lock = lock_state(&self.state);
+        if lock.is_closed() || lock.stream.closed {
+            return;
+        }
+        lock.as_mut().push(item);
+        lock.as_mut().wake_all(self.id);
+    }
+
+    /// Terminates the receivers after they received the buffered items, even if other senders still exist
+    pub fn close(&self) {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().project().stream.get_mut().closed = true;
+        lock.as_mut().wake_all(self.id);
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        lock_state(&self.state)
+            .as_mut()
+            .project()
+            .stream
+            .get_mut()
+            .senders += 1;
+        Self {
+            id: create_id(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = try_lock_state(&self.state) {
+            let source = lock.as_mut().project().stream.get_mut();
+            source.senders -= 1;
+            if source.senders == 0 {
+                source.closed = true;
+                lock.as_mut().wake_all(self.id);
+            }
+        }
+    }
+}
+
+impl<T: Clone> Sink<T> for Sender<T> {
+    type Error = std::convert::Infallible;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // Channels drop the oldest items of slow receivers, so sending never waits
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        self.push(item);
+        Ok(())
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.close();
+        Poll::Ready(Ok(()))
+    }
+}
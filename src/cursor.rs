@@ -0,0 +1,59 @@
+use futures::stream::{FusedStream, Stream};
+use std::{ops::DerefMut, pin::Pin, task::Poll};
+
+use super::{broadast_next, create_id, into_owned, lock_state, try_lock_state, StreamBroadcast};
+
+/// Created by [cursor](crate::StreamBroadcast::cursor)
+///
+/// Reads the same stream as the [StreamBroadcast] it borrows, but keeps its own position.
+pub struct BroadcastCursor<'a, T: Stream> {
+    pos: u64,
+    id: u64,
+    broadcast: &'a StreamBroadcast<T>,
+}
+
+impl<'a, T: Stream> BroadcastCursor<'a, T> {
+    pub(crate) fn new(broadcast: &'a StreamBroadcast<T>) -> Self {
+        let id = create_id();
+        let mut lock = lock_state(&broadcast.state);
+        let pos = lock.start_pos();
+        lock.as_mut().subscribe(id, pos, true);
+        drop(lock);
+        Self { pos, id, broadcast }
+    }
+}
+
+impl<T: Stream> Drop for BroadcastCursor<'_, T> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = try_lock_state(&self.broadcast.state) {
+            lock.as_mut().unsubscribe(self.id);
+        }
+    }
+}
+
+impl<T: Stream> Stream for BroadcastCursor<'_, T>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut lock = lock_state(&this.broadcast.state);
+        let polled = broadast_next(lock.deref_mut().as_mut(), cx, &mut this.pos, this.id);
+        drop(lock);
+        into_owned(polled)
+    }
+}
+
+impl<T: Stream> FusedStream for BroadcastCursor<'_, T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.broadcast.is_terminated()
+    }
+}
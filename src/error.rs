@@ -0,0 +1,42 @@
+/// Error returned by [FallibleBroadcast](crate::FallibleBroadcast)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BroadcastError {
+    /// The broadcast fell behind and `skipped` items were no longer buffered
+    Lagged { skipped: u64 },
+}
+
+impl std::fmt::Display for BroadcastError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BroadcastError::Lagged { skipped } => {
+                write!(f, "broadcast lagged behind and skipped {skipped} items")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BroadcastError {}
+
+/// Error returned by [subscribe_at](crate::StreamBroadcast::subscribe_at), if the position isn't buffered
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutOfRange {
+    /// The requested position
+    pub pos: u64,
+    /// Positions at which a broadcast could have been created
+    pub available: std::ops::RangeInclusive<u64>,
+}
+
+impl std::fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "position {} is outside of the buffered positions {}..={}",
+            self.pos,
+            self.available.start(),
+            self.available.end()
+        )
+    }
+}
+
+impl std::error::Error for OutOfRange {}
@@ -0,0 +1,82 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{pin::Pin, task::Poll};
+
+use super::StreamBroadcast;
+
+/// Item of [EventBroadcast], which makes lagging explicit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BroadcastEvent<I> {
+    Item(I),
+    /// The given number of items were skipped, because they were no longer buffered
+    Lagged(u64),
+    /// The underlying stream terminated. This is the last event.
+    Closed,
+}
+
+/// Created by [into_events](crate::StreamBroadcast::into_events)
+#[pin_project]
+pub struct EventBroadcast<T: Stream> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    pending: Option<T::Item>,
+    closed: bool,
+}
+
+impl<T: Stream> EventBroadcast<T> {
+    /// Returns the underlying broadcast. An item delayed by a preceding `Lagged` is lost.
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream> From<StreamBroadcast<T>> for EventBroadcast<T> {
+    fn from(inner: StreamBroadcast<T>) -> Self {
+        Self {
+            inner: inner.per_item(),
+            pending: None,
+            closed: false,
+        }
+    }
+}
+
+impl<T: Stream> Stream for EventBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = BroadcastEvent<T::Item>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Some(x) = this.pending.take() {
+            return Poll::Ready(Some(BroadcastEvent::Item(x)));
+        }
+        if *this.closed {
+            return Poll::Ready(None);
+        }
+        match this.inner.poll_next(cx) {
+            Poll::Ready(Some((0, x))) => Poll::Ready(Some(BroadcastEvent::Item(x))),
+            Poll::Ready(Some((skipped, x))) => {
+                *this.pending = Some(x);
+                Poll::Ready(Some(BroadcastEvent::Lagged(skipped)))
+            }
+            Poll::Ready(None) => {
+                *this.closed = true;
+                Poll::Ready(Some(BroadcastEvent::Closed))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: Stream> FusedStream for EventBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.closed && self.pending.is_none()
+    }
+}
@@ -0,0 +1,66 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{pin::Pin, task::Poll};
+
+use super::{BroadcastError, StreamBroadcast};
+
+/// Created by [fallible](crate::StreamBroadcast::fallible)
+///
+/// Yields `Err(BroadcastError::Lagged { .. })` once before the first item after a skip, and `Ok` items otherwise.
+#[pin_project]
+pub struct FallibleBroadcast<T: Stream> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    pending: Option<T::Item>,
+}
+
+impl<T: Stream> FallibleBroadcast<T> {
+    /// Returns the underlying broadcast. An item delayed by a preceding `Err` is lost.
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream> From<StreamBroadcast<T>> for FallibleBroadcast<T> {
+    fn from(inner: StreamBroadcast<T>) -> Self {
+        Self {
+            inner: inner.per_item(),
+            pending: None,
+        }
+    }
+}
+
+impl<T: Stream> Stream for FallibleBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = Result<T::Item, BroadcastError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Some(x) = this.pending.take() {
+            return Poll::Ready(Some(Ok(x)));
+        }
+        match this.inner.poll_next(cx) {
+            Poll::Ready(Some((0, x))) => Poll::Ready(Some(Ok(x))),
+            Poll::Ready(Some((skipped, x))) => {
+                *this.pending = Some(x);
+                Poll::Ready(Some(Err(BroadcastError::Lagged { skipped })))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: Stream> FusedStream for FallibleBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.pending.is_none() && self.inner.is_terminated()
+    }
+}
@@ -0,0 +1,79 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{ops::DerefMut, pin::Pin, task::Poll};
+
+use super::{broadcast_next_with, lock_state, StreamBroadcast};
+
+/// Created by [subscribe_filter](crate::StreamBroadcast::subscribe_filter)
+///
+/// Yields only the items matching the predicate. Items which don't match aren't cloned and don't count as skipped.
+#[pin_project]
+pub struct FilteredBroadcast<T: Stream, F> {
+    inner: StreamBroadcast<T>,
+    predicate: F,
+    /// Items skipped since the last yielded item
+    skipped: u64,
+}
+
+impl<T: Stream, F> FilteredBroadcast<T, F> {
+    pub(crate) fn new(inner: StreamBroadcast<T>, predicate: F) -> Self {
+        Self {
+            inner,
+            predicate,
+            skipped: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream, F> Stream for FilteredBroadcast<T, F>
+where
+    T::Item: Clone,
+    F: FnMut(&T::Item) -> bool,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let inner = this.inner;
+        let mut lock = lock_state(&inner.state);
+        lock.as_mut().expire();
+        if lock.is_beyond(inner.pos, inner.close_after) {
+            return Poll::Ready(None);
+        }
+        let predicate = this.predicate;
+        let mut read = |x: &T::Item| predicate(x).then(|| x.clone());
+        broadcast_next_with(
+            lock.deref_mut().as_mut(),
+            cx,
+            &mut inner.pos,
+            inner.id,
+            this.skipped,
+            &mut read,
+        )
+        .map(|x| {
+            x.map(|(offset, x)| {
+                (
+                    inner.offset_mode.report(&mut inner.skipped_total, offset),
+                    x,
+                )
+            })
+        })
+    }
+}
+
+impl<T: Stream, F> FusedStream for FilteredBroadcast<T, F>
+where
+    T::Item: Clone,
+    F: FnMut(&T::Item) -> bool,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
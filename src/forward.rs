@@ -0,0 +1,46 @@
+use futures::{stream::Stream, Sink, SinkExt, StreamExt};
+use std::future::Future;
+
+use super::StreamBroadcast;
+
+impl<T: Stream> StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    /// Forwards the items to all `sinks` concurrently and returns the result of each sink in the same order
+    ///
+    /// Each sink gets its own clone, which starts at the same position as a clone created by this call.
+    /// Up to `buffer` items are buffered in front of each sink, so a sink which is temporarily slow doesn't
+    /// lag behind immediately. A failing sink drops its clone, so it doesn't hold back the others.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..3).fuse().broadcast(3);
+    /// let (tx1, rx1) = futures::channel::mpsc::unbounded();
+    /// let (tx2, rx2) = futures::channel::mpsc::unbounded();
+    /// let forwarded = broadcast.forward_all([tx1, tx2], 1);
+    /// drop(broadcast);
+    /// assert!(forwarded.await.iter().all(Result::is_ok));
+    /// assert_eq!(vec![(0, 0), (0, 1), (0, 2)], rx1.collect::<Vec<_>>().await);
+    /// assert_eq!(3, rx2.count().await);
+    /// # }
+    /// ```
+    pub fn forward_all<S>(
+        &self,
+        sinks: impl IntoIterator<Item = S>,
+        buffer: usize,
+    ) -> impl Future<Output = Vec<Result<(), S::Error>>>
+    where
+        S: Sink<(u64, T::Item)>,
+    {
+        let forwards = sinks
+            .into_iter()
+            .map(|sink| self.clone().map(Ok).forward(sink.buffer(buffer)))
+            .collect::<Vec<_>>();
+        futures::future::join_all(forwards)
+    }
+}
@@ -0,0 +1,57 @@
+use futures::stream::Stream;
+
+use super::{lock_state, SharedState, StreamBroadcast};
+
+/// Created by [keep_alive](crate::StreamBroadcast::keep_alive)
+///
+/// Keeps the underlying stream and its buffered items alive like a [StreamBroadcast], without receiving items.
+/// Therefore it never lags behind and doesn't hold back the underlying stream with backpressure.
+pub struct BroadcastHandle<T: Stream> {
+    state: SharedState<T>,
+}
+
+impl<T: Stream> BroadcastHandle<T> {
+    pub(crate) fn new(state: SharedState<T>) -> Self {
+        Self { state }
+    }
+
+    /// Creates a [StreamBroadcast], which starts reading like a clone of a [StreamBroadcast]
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{StartPos, StreamBroadcastExt};
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5)
+    ///     .fuse()
+    ///     .broadcast_builder()
+    ///     .capacity(2)
+    ///     .start_at(StartPos::Oldest)
+    ///     .build();
+    /// let handle = broadcast.keep_alive();
+    /// (&mut broadcast).take(3).count().await;
+    /// let mut weak = broadcast.downgrade();
+    /// drop(broadcast);
+    /// assert_eq!(Some((0, 3)), weak.next().await);
+    /// assert_eq!(vec![(0, 2), (0, 3), (0, 4)], handle.subscribe().collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn subscribe(&self) -> StreamBroadcast<T> {
+        let pos = lock_state(&self.state).start_pos();
+        StreamBroadcast::with_pos(self.state.clone(), pos)
+    }
+
+    /// Replaces the underlying stream, see [StreamBroadcast::swap_source]
+    pub fn swap_source(&self, stream: T) {
+        lock_state(&self.state).as_mut().swap_source(stream);
+    }
+}
+
+impl<T: Stream> Clone for BroadcastHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+
+type HeartbeatFn<I> = Box<dyn FnMut() -> I + Send>;
+
+/// Produces an item, if the underlying stream was idle for `interval`. Set by [BroadcastBuilder::heartbeat](crate::BroadcastBuilder::heartbeat).
+pub(crate) struct Heartbeat<I> {
+    interval: Duration,
+    factory: HeartbeatFn<I>,
+    /// Created on the first poll, because a broadcast might be built outside of a runtime
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<I> Heartbeat<I> {
+    pub(crate) fn new(interval: Duration, factory: impl FnMut() -> I + Send + 'static) -> Self {
+        Self {
+            interval,
+            factory: Box::new(factory),
+            sleep: None,
+        }
+    }
+
+    /// Restarts the interval after an item of the underlying stream
+    pub(crate) fn reset(&mut self) {
+        if let Some(sleep) = &mut self.sleep {
+            sleep.as_mut().reset(Instant::now() + self.interval);
+        }
+    }
+
+    pub(crate) fn poll(&mut self, cx: &mut std::task::Context<'_>) -> Poll<I> {
+        let interval = self.interval;
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(interval)));
+        ready!(sleep.as_mut().poll(cx));
+        sleep.as_mut().reset(Instant::now() + interval);
+        Poll::Ready((self.factory)())
+    }
+}
@@ -0,0 +1,70 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{pin::Pin, task::Poll};
+
+use super::StreamBroadcast;
+
+/// Created by [items](crate::StreamBroadcast::items)
+///
+/// Yields the items without the offset. Skipped items are counted in [skipped](Self::skipped) instead.
+#[pin_project]
+pub struct ItemBroadcast<T: Stream> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    skipped: u64,
+}
+
+impl<T: Stream> ItemBroadcast<T> {
+    /// Number of items skipped, because this broadcast fell behind
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream> From<StreamBroadcast<T>> for ItemBroadcast<T> {
+    fn from(inner: StreamBroadcast<T>) -> Self {
+        Self {
+            inner: inner.per_item(),
+            skipped: 0,
+        }
+    }
+}
+
+impl<T: Stream> Clone for ItemBroadcast<T> {
+    fn clone(&self) -> Self {
+        self.inner.clone().into()
+    }
+}
+
+impl<T: Stream> Stream for ItemBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = T::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.inner.poll_next(cx).map(|x| {
+            x.map(|(skipped, x)| {
+                *this.skipped += skipped;
+                x
+            })
+        })
+    }
+}
+
+impl<T: Stream> FusedStream for ItemBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
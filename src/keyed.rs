@@ -0,0 +1,264 @@
+use futures::stream::{FusedStream, Stream};
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Poll, Waker},
+};
+
+use super::{create_id, PoisonPolicy};
+
+type KeyFn<I, K> = Box<dyn FnMut(&I) -> K + Send>;
+
+/// Created by [broadcast_by_key](crate::StreamBroadcastExt::broadcast_by_key)
+///
+/// Partitions the items of the underlying stream by key. Each key has its own buffer of `size` items,
+/// which only exists while the key is subscribed.
+pub struct KeyedStreamBroadcast<T: Stream, K> {
+    state: Arc<Mutex<KeyedState<T, K>>>,
+}
+
+struct KeyedState<T: Stream, K> {
+    stream: Pin<Box<T>>,
+    key: KeyFn<T::Item, K>,
+    size: usize,
+    rings: HashMap<K, KeyRing<T::Item>>,
+    wakable: HashMap<u64, Waker>,
+    /// Set when `stream` returned `None`, so it is never polled again
+    terminated: bool,
+    poison_policy: PoisonPolicy,
+}
+
+/// Buffered items of a single key, where the item at `global_pos - 1` is the last one in `items`
+struct KeyRing<I> {
+    items: VecDeque<I>,
+    global_pos: u64,
+    subscribers: usize,
+}
+
+impl<T: Stream, K: Hash + Eq + Clone> KeyedStreamBroadcast<T, K> {
+    pub(crate) fn new(
+        stream: T,
+        size: usize,
+        key: impl FnMut(&T::Item) -> K + Send + 'static,
+    ) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(KeyedState {
+                stream: Box::pin(stream),
+                key: Box::new(key),
+                size,
+                rings: Default::default(),
+                wakable: Default::default(),
+                terminated: false,
+                poison_policy: PoisonPolicy::Propagate,
+            })),
+        }
+    }
+
+    /// Sets how the subscriptions continue after a panic poisoned their shared state, see
+    /// [BroadcastBuilder::poison_policy](crate::BroadcastBuilder::poison_policy)
+    ///
+    /// [PoisonPolicy::Terminate] drops the buffered items of all keys.
+    pub fn poison_policy(self, policy: PoisonPolicy) -> Self {
+        lock_keyed(&self.state).poison_policy = policy;
+        self
+    }
+
+    /// Creates a stream of all items with key `key`, which are produced after this call
+    ///
+    /// The `u64` in the items is the number of items of this key skipped right before the item.
+    pub fn subscribe_key(&self, key: K) -> KeyedSubscription<T, K> {
+        KeyedSubscription::new(self.state.clone(), key)
+    }
+}
+
+impl<T: Stream, K> Clone for KeyedStreamBroadcast<T, K> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T: Stream, K: Hash + Eq> KeyedState<T, K> {
+    fn push(&mut self, x: T::Item) {
+        let key = (self.key)(&x);
+        if let Some(ring) = self.rings.get_mut(&key) {
+            if ring.items.len() == self.size {
+                ring.items.pop_front();
+            }
+            ring.items.push_back(x);
+            ring.global_pos += 1;
+        }
+    }
+
+    /// Drops the buffered items and terminates all subscriptions
+    fn terminate(&mut self) {
+        self.rings.values_mut().for_each(|ring| ring.items.clear());
+        self.terminated = true;
+        self.wake_all(u64::MAX);
+    }
+
+    fn wake_all(&mut self, except: u64) {
+        self.wakable.drain().for_each(|(k, w)| {
+            if k != except {
+                w.wake();
+            }
+        });
+    }
+}
+
+/// Locks the shared state and handles a poisoned lock according to its [PoisonPolicy], like the state of a
+/// [StreamBroadcast](crate::StreamBroadcast)
+///
+/// # Panics
+/// Panics if the lock is poisoned and the policy is [PoisonPolicy::Propagate]
+fn lock_keyed<T: Stream, K: Hash + Eq>(
+    state: &Mutex<KeyedState<T, K>>,
+) -> MutexGuard<'_, KeyedState<T, K>> {
+    try_lock_keyed(state).expect("A panic poisoned the shared state of the broadcast")
+}
+
+/// Like [lock_keyed], but returns `None` instead of panicking
+fn try_lock_keyed<T: Stream, K: Hash + Eq>(
+    state: &Mutex<KeyedState<T, K>>,
+) -> Option<MutexGuard<'_, KeyedState<T, K>>> {
+    match state.lock() {
+        Ok(lock) => Some(lock),
+        Err(poisoned) => {
+            let mut lock = poisoned.into_inner();
+            match lock.poison_policy {
+                PoisonPolicy::Propagate => return None,
+                PoisonPolicy::Terminate => lock.terminate(),
+                PoisonPolicy::Recover => {}
+            }
+            state.clear_poison();
+            Some(lock)
+        }
+    }
+}
+
+/// Created by [subscribe_key](KeyedStreamBroadcast::subscribe_key)
+pub struct KeyedSubscription<T: Stream, K: Hash + Eq> {
+    key: K,
+    pos: u64,
+    id: u64,
+    state: Arc<Mutex<KeyedState<T, K>>>,
+}
+
+impl<T: Stream, K: Hash + Eq + Clone> KeyedSubscription<T, K> {
+    fn new(state: Arc<Mutex<KeyedState<T, K>>>, key: K) -> Self {
+        let mut lock = lock_keyed(&state);
+        let ring = lock.rings.entry(key.clone()).or_insert_with(|| KeyRing {
+            items: Default::default(),
+            global_pos: 0,
+            subscribers: 0,
+        });
+        ring.subscribers += 1;
+        let pos = ring.global_pos;
+        drop(lock);
+        Self {
+            key,
+            pos,
+            id: create_id(),
+            state,
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Number of buffered items of this key, which this subscription didn't receive yet
+    pub fn lag(&self) -> u64 {
+        let lock = lock_keyed(&self.state);
+        let ring = &lock.rings[&self.key];
+        ring.global_pos - self.pos.min(ring.global_pos)
+    }
+}
+
+impl<T: Stream, K: Hash + Eq + Clone> Clone for KeyedSubscription<T, K> {
+    fn clone(&self) -> Self {
+        Self::new(self.state.clone(), self.key.clone())
+    }
+}
+
+impl<T: Stream, K: Hash + Eq> Drop for KeyedSubscription<T, K> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = try_lock_keyed(&self.state) {
+            lock.wakable.remove(&self.id);
+            if let Some(ring) = lock.rings.get_mut(&self.key) {
+                ring.subscribers -= 1;
+                if ring.subscribers == 0 {
+                    lock.rings.remove(&self.key);
+                }
+            }
+        }
+    }
+}
+
+// The key is never pinned
+impl<T: Stream, K: Hash + Eq> Unpin for KeyedSubscription<T, K> {}
+
+impl<T: Stream, K: Hash + Eq> Stream for KeyedSubscription<T, K>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut lock = lock_keyed(&this.state);
+        loop {
+            let ring = &lock.rings[&this.key];
+            let oldest = ring.global_pos - ring.items.len() as u64;
+            let return_pos = this.pos.max(oldest);
+            if return_pos < ring.global_pos {
+                let offset = return_pos - this.pos;
+                this.pos = return_pos + 1;
+                let x = ring.items[(return_pos - oldest) as usize].clone();
+                return Poll::Ready(Some((offset, x)));
+            }
+            if lock.terminated {
+                return Poll::Ready(None);
+            }
+            let stream = lock.stream.as_mut();
+            // A panic would poison the lock for all subscribers, so the broadcast terminates instead
+            let polled = std::panic::catch_unwind(AssertUnwindSafe(|| stream.poll_next(cx)))
+                .unwrap_or_else(|_| {
+                    event!(warn, "underlying stream panicked");
+                    Poll::Ready(None)
+                });
+            match polled {
+                Poll::Ready(Some(x)) => {
+                    lock.push(x);
+                    lock.wake_all(this.id);
+                }
+                Poll::Ready(None) => {
+                    lock.terminated = true;
+                    lock.wake_all(this.id);
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => {
+                    lock.wakable.insert(this.id, cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<T: Stream, K: Hash + Eq> FusedStream for KeyedSubscription<T, K>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        let lock = lock_keyed(&self.state);
+        lock.terminated && self.pos >= lock.rings[&self.key].global_pos
+    }
+}
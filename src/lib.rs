@@ -1,59 +1,574 @@
 #![doc = include_str!("../README.md")]
 #![forbid(unsafe_code)]
 
-use futures::stream::{FusedStream, Stream};
+use futures::{
+    channel::mpsc::{UnboundedReceiver, UnboundedSender},
+    stream::{FusedStream, Stream},
+};
 use pin_project::pin_project;
 use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    hash::Hash,
     ops::DerefMut,
-    pin::{pin, Pin},
-    sync::{atomic::AtomicU64, Arc, Mutex},
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
     task::Poll,
+    time::{Duration, Instant},
 };
 
+/// Emits a `tracing` event at `$level`, if the `tracing` feature is enabled
+macro_rules! event {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)*);
+    };
+}
+
+mod backfill;
+mod builder;
+#[cfg(feature = "bytes")]
+mod bytes;
+#[cfg(feature = "tokio-util")]
+mod cancel;
+mod channel;
+mod cursor;
+mod error;
+mod event;
+mod fallible;
+mod filter;
+mod forward;
+mod handle;
+#[cfg(feature = "tokio")]
+mod heartbeat;
+mod items;
+mod keyed;
+mod local;
+mod mapped;
+mod merge;
+mod metrics;
+mod pool;
+#[cfg(feature = "recorder")]
+mod recorder;
+mod relay;
+mod shared;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "spill")]
+mod spill;
+mod stateful;
+mod stats;
+mod storage;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod throttle;
+#[cfg(feature = "tokio")]
+mod timeout;
+#[cfg(feature = "tokio")]
+mod tokio_receiver;
+mod try_broadcast;
 mod weak;
 
+pub use backfill::*;
+pub use builder::*;
+#[cfg(feature = "bytes")]
+pub use bytes::*;
+pub use channel::*;
+pub use cursor::*;
+pub use error::*;
+pub use event::*;
+pub use fallible::*;
+pub use filter::*;
+pub use handle::*;
+pub use items::*;
+pub use keyed::*;
+pub use local::*;
+pub use mapped::*;
+pub use merge::*;
+pub use metrics::*;
+pub use pool::*;
+#[cfg(feature = "recorder")]
+pub use recorder::*;
+pub use relay::*;
+pub use shared::*;
+#[cfg(feature = "serde")]
+pub use snapshot::*;
+#[cfg(feature = "spill")]
+pub use spill::*;
+pub use stateful::*;
+pub use stats::*;
+pub use storage::*;
+#[cfg(feature = "test-util")]
+pub use test_util::*;
+pub use throttle::*;
+#[cfg(feature = "tokio")]
+pub use timeout::*;
+#[cfg(feature = "tokio")]
+pub use tokio_receiver::*;
+pub use try_broadcast::*;
 pub use weak::*;
 
-pub trait StreamBroadcastExt: FusedStream + Sized {
+pub trait StreamBroadcastExt: Stream + Sized {
     fn broadcast(self, size: usize) -> StreamBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but calls `f` exactly once for each item received from the underlying stream
+    ///
+    /// In contrast to [StreamExt::inspect](futures::StreamExt::inspect) on each broadcast, `f` is called once per item,
+    /// no matter how many broadcasts receive it. Items read from the cache don't trigger `f` again.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    ///
+    /// let counter = Arc::new(AtomicUsize::new(0));
+    /// let counter2 = counter.clone();
+    /// let broadcast = futures::stream::iter(0..3)
+    ///     .fuse()
+    ///     .broadcast_inspect(3, move |_| { counter2.fetch_add(1, Ordering::SeqCst); });
+    /// let broadcast2 = broadcast.clone();
+    /// assert_eq!(3, broadcast.count().await);
+    /// assert_eq!(3, broadcast2.count().await);
+    /// assert_eq!(3, counter.load(Ordering::SeqCst));
+    /// # }
+    /// ```
+    fn broadcast_inspect(
+        self,
+        size: usize,
+        f: impl FnMut(&Self::Item) + Send + 'static,
+    ) -> StreamBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but never skips items for [StreamBroadcast]s
+    ///
+    /// Instead of overwriting the oldest item, the underlying stream isn't polled anymore while the slowest
+    /// [StreamBroadcast] is `size` items behind. [WeakStreamBroadcast]s are not considered and might still skip items.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut fast = futures::stream::iter(0..3).fuse().broadcast_with_backpressure(1);
+    /// let slow = fast.clone();
+    /// assert_eq!(Some((0, 0)), fast.next().await);
+    /// assert!(futures::poll!(fast.next()).is_pending());
+    /// let (fast, slow) = futures::join!(fast.collect::<Vec<_>>(), slow.collect::<Vec<_>>());
+    /// assert_eq!(vec![(0, 1), (0, 2)], fast);
+    /// assert_eq!(vec![(0, 0), (0, 1), (0, 2)], slow);
+    /// # }
+    /// ```
+    fn broadcast_with_backpressure(self, size: usize) -> StreamBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but yields the items without the number of skipped items
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter('a'..='c').fuse().broadcast_items(3);
+    /// assert_eq!("abc", broadcast.collect::<String>().await);
+    /// # }
+    /// ```
+    fn broadcast_items(self, size: usize) -> ItemBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but caches each item in an [Arc], which the broadcasts receive
+    ///
+    /// Receiving an item only increments the reference count, no matter how expensive cloning the item is.
+    /// Use [broadcast_shared](SharedBroadcastExt::broadcast_shared) for items, which don't implement [Clone].
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::sync::Arc;
+    ///
+    /// let mut broadcast = futures::stream::iter([vec![0u8; 4096]]).fuse().broadcast_arc(3);
+    /// let mut broadcast2 = broadcast.clone();
+    /// let (_, first) = broadcast.next().await.unwrap();
+    /// let (_, second) = broadcast2.next().await.unwrap();
+    /// assert!(Arc::ptr_eq(&first, &second));
+    /// # }
+    /// ```
+    fn broadcast_arc(self, size: usize) -> ArcBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but bounds the cache by the total weight of the buffered items
+    ///
+    /// `weigh` is called once for each received item. The oldest items are evicted until the new item fits
+    /// into `max_weight`. An item heavier than `max_weight` is still buffered on its own.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(["a", "bb", "ccc", "dddd"])
+    ///     .fuse()
+    ///     .broadcast_weighted(5, |x| x.len());
+    /// let slow = broadcast.clone();
+    /// assert_eq!(4, (&mut broadcast).count().await);
+    /// assert_eq!(vec![(3, "dddd")], slow.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    fn broadcast_weighted(
+        self,
+        max_weight: usize,
+        weigh: impl Fn(&Self::Item) -> usize + Send + 'static,
+    ) -> StreamBroadcast<Self>;
+
+    /// Creates a broadcast, which only keeps the most recent item, like a watch channel
+    ///
+    /// New clones immediately receive the current item. Afterwards, only items newer than the last received one
+    /// are yielded and intermediate items are skipped.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast_latest();
+    /// let mut lagging = broadcast.clone();
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// assert_eq!(Some((0, 0)), broadcast.clone().next().await);
+    /// assert_eq!(3, (&mut broadcast).take(3).count().await);
+    /// assert_eq!(Some((3, 3)), lagging.next().await);
+    /// # }
+    /// ```
+    fn broadcast_latest(self) -> StreamBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but each item is delivered to only one of the clones
+    ///
+    /// Clones compete for the items, which allows to share the work of a stream between multiple consumers.
+    /// The offsets count the items, which were overwritten before any clone received them.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut first = futures::stream::iter(0..5).fuse().distribute(5);
+    /// let mut second = first.clone();
+    /// assert_eq!(Some((0, 0)), first.next().await);
+    /// assert_eq!(Some((0, 1)), second.next().await);
+    /// assert_eq!(Some((0, 2)), first.next().await);
+    /// assert_eq!(vec![(0, 3), (0, 4)], second.collect::<Vec<_>>().await);
+    /// assert_eq!(None, first.next().await);
+    /// # }
+    /// ```
+    fn distribute(self, size: usize) -> StreamBroadcast<Self>;
+
+    /// Like [distribute](Self::distribute), but item `n` is delivered to the `n % k`th of `k` clones
+    ///
+    /// Items are assigned when they are received from the underlying stream, based on the clones existing at that time
+    /// in the order of their creation. [Paused](StreamBroadcast::pause) clones, [weak](StreamBroadcast::downgrade)
+    /// broadcasts and [cursors](StreamBroadcast::cursor) aren't assigned items. In contrast to
+    /// [distribute](Self::distribute), fast clones don't take items away from slow ones, so the items are spread
+    /// evenly. The offsets count the assigned items, which were evicted.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let first = futures::stream::iter(0..6).fuse().round_robin(6);
+    /// let second = first.clone();
+    /// let (first, second) = futures::join!(first.collect::<Vec<_>>(), second.collect::<Vec<_>>());
+    /// assert_eq!(vec![(0, 0), (0, 2), (0, 4)], first);
+    /// assert_eq!(vec![(0, 1), (0, 3), (0, 5)], second);
+    /// # }
+    /// ```
+    fn round_robin(self, size: usize) -> StreamBroadcast<Self>;
+
+    /// Partitions the items by `key`, so each subscriber only receives the items of a single key
+    ///
+    /// Every subscribed key buffers up to `size` items. Items of keys without subscribers are dropped.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let keyed = futures::stream::iter(0..10).fuse().broadcast_by_key(5, |x| x % 3);
+    /// let zeros = keyed.subscribe_key(0);
+    /// assert_eq!(vec![(0, 0), (0, 3), (0, 6), (0, 9)], zeros.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    fn broadcast_by_key<K: Hash + Eq + Clone>(
+        self,
+        size: usize,
+        key: impl FnMut(&Self::Item) -> K + Send + 'static,
+    ) -> KeyedStreamBroadcast<Self, K>;
+
+    /// Like [broadcast](Self::broadcast), but spawns a task, which polls the underlying stream eagerly
+    ///
+    /// Subscribers only read from the cache, so a paused subscriber never stalls the others. See
+    /// [pump](StreamBroadcast::pump) for details. The task stops when the underlying stream terminates or when it
+    /// receives an item after all broadcasts were dropped.
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..5).fuse().broadcast_driven(5);
+    /// tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    /// assert_eq!(5, broadcast.stats().current_len);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    fn broadcast_driven(self, size: usize) -> StreamBroadcast<Self>
+    where
+        Self: Send + 'static,
+        Self::Item: Send;
+
+    /// Like [broadcast](Self::broadcast), but for single threaded executors, see [LocalStreamBroadcast]
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::rc::Rc;
+    ///
+    /// let broadcast = futures::stream::iter([Rc::new(1), Rc::new(2)]).broadcast_local(3);
+    /// let broadcast2 = broadcast.clone();
+    /// assert_eq!(2, broadcast.count().await);
+    /// assert_eq!(vec![(0, Rc::new(1)), (0, Rc::new(2))], broadcast2.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    fn broadcast_local(self, size: usize) -> LocalStreamBroadcast<Self>;
+
+    /// Like [broadcast](Self::broadcast), but folds all items into a state, which new clones receive first
+    ///
+    /// This suits streams of deltas: A late subscriber receives a [StateEvent::Snapshot] of all items so far,
+    /// followed by the items produced after it subscribed. Items are folded once, when they enter the cache.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{StateEvent, StreamBroadcastExt};
+    ///
+    /// let mut sums = futures::stream::iter(1..=4).fuse().broadcast_with_state(3, 0, |sum, x| *sum += x);
+    /// assert_eq!(Some(StateEvent::Snapshot(0)), sums.next().await);
+    /// assert_eq!(Some(StateEvent::Delta(0, 1)), sums.next().await);
+    /// assert_eq!(Some(StateEvent::Delta(0, 2)), sums.next().await);
+    /// let late = sums.clone();
+    /// assert_eq!(
+    ///     vec![StateEvent::Snapshot(3), StateEvent::Delta(0, 3), StateEvent::Delta(0, 4)],
+    ///     late.collect::<Vec<_>>().await
+    /// );
+    /// # }
+    /// ```
+    fn broadcast_with_state<S: Clone + Send + 'static>(
+        self,
+        size: usize,
+        initial: S,
+        fold: impl FnMut(&mut S, &Self::Item) + Send + 'static,
+    ) -> StatefulBroadcast<Self, S>;
+
+    /// Creates a [BroadcastBuilder] to configure more than the size of the broadcast
+    fn broadcast_builder(self) -> BroadcastBuilder<Self>;
 }
 
-impl<T: FusedStream + Sized> StreamBroadcastExt for T
+impl<T: Stream + Sized> StreamBroadcastExt for T
 where
     T::Item: Clone,
 {
     fn broadcast(self, size: usize) -> StreamBroadcast<Self> {
         StreamBroadcast::new(self, size)
     }
+
+    fn broadcast_inspect(
+        self,
+        size: usize,
+        f: impl FnMut(&Self::Item) + Send + 'static,
+    ) -> StreamBroadcast<Self> {
+        self.broadcast_builder().capacity(size).inspect(f).build()
+    }
+
+    fn broadcast_with_backpressure(self, size: usize) -> StreamBroadcast<Self> {
+        self.broadcast_builder()
+            .capacity(size)
+            .lag_policy(LagPolicy::Backpressure)
+            .build()
+    }
+
+    fn broadcast_items(self, size: usize) -> ItemBroadcast<Self> {
+        self.broadcast(size).items()
+    }
+
+    fn broadcast_arc(self, size: usize) -> ArcBroadcast<Self> {
+        self.broadcast_shared(size)
+    }
+
+    fn broadcast_weighted(
+        self,
+        max_weight: usize,
+        weigh: impl Fn(&Self::Item) -> usize + Send + 'static,
+    ) -> StreamBroadcast<Self> {
+        self.broadcast_builder()
+            .max_weight(max_weight, weigh)
+            .build()
+    }
+
+    fn broadcast_latest(self) -> StreamBroadcast<Self> {
+        self.broadcast_builder()
+            .capacity(1)
+            .start_at(StartPos::Oldest)
+            .build()
+    }
+
+    fn broadcast_by_key<K: Hash + Eq + Clone>(
+        self,
+        size: usize,
+        key: impl FnMut(&Self::Item) -> K + Send + 'static,
+    ) -> KeyedStreamBroadcast<Self, K> {
+        KeyedStreamBroadcast::new(self, size, key)
+    }
+
+    fn round_robin(self, size: usize) -> StreamBroadcast<Self> {
+        self.broadcast_builder()
+            .capacity(size)
+            .delivery(Delivery::RoundRobin)
+            .build()
+    }
+
+    fn distribute(self, size: usize) -> StreamBroadcast<Self> {
+        self.broadcast_builder()
+            .capacity(size)
+            .delivery(Delivery::Distribute)
+            .build()
+    }
+
+    #[cfg(feature = "tokio")]
+    fn broadcast_driven(self, size: usize) -> StreamBroadcast<Self>
+    where
+        Self: Send + 'static,
+        Self::Item: Send,
+    {
+        let broadcast = self.broadcast(size);
+        tokio::spawn(Pump::new(&broadcast.state, false));
+        broadcast
+    }
+
+    fn broadcast_local(self, size: usize) -> LocalStreamBroadcast<Self> {
+        LocalStreamBroadcast::new(self, size)
+    }
+
+    fn broadcast_with_state<S: Clone + Send + 'static>(
+        self,
+        size: usize,
+        initial: S,
+        fold: impl FnMut(&mut S, &Self::Item) + Send + 'static,
+    ) -> StatefulBroadcast<Self, S> {
+        StatefulBroadcast::new(self.broadcast(size), initial, fold)
+    }
+
+    fn broadcast_builder(self) -> BroadcastBuilder<Self> {
+        BroadcastBuilder::new(self)
+    }
 }
 
-#[pin_project]
-pub struct StreamBroadcast<T: FusedStream> {
+/// Meaning of the `u64` in the items of a [StreamBroadcast]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OffsetMode {
+    /// Number of items skipped right before this item
+    #[default]
+    PerItem,
+    /// Total number of items skipped by this broadcast so far
+    Cumulative,
+}
+
+impl OffsetMode {
+    fn report(self, skipped_total: &mut u64, offset: u64) -> u64 {
+        *skipped_total += offset;
+        match self {
+            OffsetMode::PerItem => offset,
+            OffsetMode::Cumulative => *skipped_total,
+        }
+    }
+}
+
+pub struct StreamBroadcast<T: Stream> {
     pos: u64,
     id: u64,
+    skipped_total: u64,
+    offset_mode: OffsetMode,
+    close_after: Option<u64>,
+    conflate: bool,
     state: Arc<Mutex<Pin<Box<StreamBroadcastState<T>>>>>,
 }
 
-impl<T: FusedStream> Clone for StreamBroadcast<T> {
+impl<T: Stream> Clone for StreamBroadcast<T> {
     fn clone(&self) -> Self {
+        let pos = lock_state(&self.state).start_pos();
+        let mut clone = Self::with_pos(self.state.clone(), pos);
+        clone.offset_mode = self.offset_mode;
+        clone
+    }
+}
+
+impl<T: Stream> Drop for StreamBroadcast<T> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = try_lock_state(&self.state) {
+            lock.as_mut().unsubscribe(self.id);
+        }
+    }
+}
+
+impl<T: Stream> StreamBroadcast<T> {
+    pub(crate) fn with_pos(state: Arc<Mutex<Pin<Box<StreamBroadcastState<T>>>>>, pos: u64) -> Self {
+        let id = create_id();
+        let mut lock = lock_state(&state);
+        lock.as_mut().subscribe(id, pos, true);
+        lock.as_mut().join_rotation(id);
+        drop(lock);
         Self {
-            state: self.state.clone(),
-            id: create_id(),
-            pos: self.state.lock().unwrap().global_pos,
+            pos,
+            id,
+            skipped_total: 0,
+            offset_mode: OffsetMode::PerItem,
+            close_after: None,
+            conflate: false,
+            state,
         }
     }
+
+    /// Switches to [OffsetMode::PerItem] for adapters, which read the offsets as the items skipped right before
+    pub(crate) fn per_item(mut self) -> Self {
+        self.offset_mode = OffsetMode::PerItem;
+        self
+    }
 }
 
-impl<T: FusedStream> StreamBroadcast<T>
+impl<T: Stream> StreamBroadcast<T>
 where
     T::Item: Clone,
 {
     pub fn new(outer: T, size: usize) -> Self {
-        Self {
-            state: Arc::new(Mutex::new(Box::pin(StreamBroadcastState::new(outer, size)))),
-            id: create_id(),
-            pos: 0,
-        }
+        Self::from_state(StreamBroadcastState::new(outer, size))
+    }
+
+    fn from_state(state: StreamBroadcastState<T>) -> Self {
+        Self::with_pos(Arc::new(Mutex::new(Box::pin(state))), 0)
     }
 
     /// Creates a weak broadcast which terminates its stream, if all 'strong' [StreamBroadcast] went out of scope
@@ -65,7 +580,7 @@ where
     /// use stream_broadcast::StreamBroadcastExt;
     ///
     /// let stream = futures::stream::iter(0..).fuse().broadcast(5);
-    /// let mut weak = std::pin::pin!(stream.weak());
+    /// let mut weak = stream.weak();
     /// assert_eq!(Some((0, 0)), weak.next().await);
     /// drop(stream);
     /// assert_eq!(None, weak.next().await);
@@ -75,127 +590,2758 @@ where
         WeakStreamBroadcast::new(Arc::downgrade(&self.state), self.pos)
     }
 
+    /// Replaces the underlying stream, e.g. to reconnect a network subscription, without disturbing the broadcasts
+    ///
+    /// The buffered items and the positions of all broadcasts are kept, so the items of `stream` are received
+    /// after the ones of the previous stream. The previous stream is dropped. Once the underlying stream
+    /// terminated, the broadcasts stay terminated, so `stream` is never polled.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..2)
+    ///     .chain(futures::stream::pending())
+    ///     .boxed()
+    ///     .fuse()
+    ///     .broadcast(4);
+    /// let slow = broadcast.clone();
+    /// assert_eq!(2, (&mut broadcast).take(2).count().await);
+    /// assert!(futures::poll!(broadcast.next()).is_pending());
+    /// broadcast.swap_source(futures::stream::iter(2..4).boxed().fuse());
+    /// assert_eq!(vec![(0, 2), (0, 3)], broadcast.collect::<Vec<_>>().await);
+    /// assert_eq!(vec![0, 1, 2, 3], slow.map(|(_, x)| x).collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn swap_source(&self, stream: T) {
+        lock_state(&self.state).as_mut().swap_source(stream);
+    }
+
+    /// Creates a handle, which keeps the underlying stream and the buffered items alive without being a [Stream]
+    ///
+    /// Unlike a clone, which isn't polled, the handle never has unread items, so it doesn't count as lagging
+    /// and doesn't hold back the underlying stream with backpressure. [Weak broadcasts](WeakStreamBroadcast)
+    /// only terminate after all [StreamBroadcast]s and handles were dropped.
+    pub fn keep_alive(&self) -> BroadcastHandle<T> {
+        BroadcastHandle::new(self.state.clone())
+    }
+
     #[deprecated(since = "0.2.2", note = "please use `downgrade` instead")]
     pub fn weak(&self) -> WeakStreamBroadcast<T> {
         WeakStreamBroadcast::new(Arc::downgrade(&self.state), self.pos)
     }
-}
-
-impl<T: FusedStream> Stream for StreamBroadcast<T>
-where
-    T::Item: Clone,
-{
-    type Item = (u64, T::Item);
 
-    fn poll_next(
-        self: Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> Poll<Option<Self::Item>> {
-        let this = self.project();
-        let mut lock = this.state.lock().unwrap();
-        broadast_next(lock.deref_mut().as_mut(), cx, this.pos, *this.id)
-    }
-}
-fn create_id() -> u64 {
-    static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
-    ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
-}
-fn broadast_next<T: FusedStream>(
-    pinned: Pin<&mut StreamBroadcastState<T>>,
-    cx: &mut std::task::Context<'_>,
-    pos: &mut u64,
-    id: u64,
-) -> Poll<Option<(u64, T::Item)>>
-where
-    T::Item: Clone,
-{
-    match pinned.poll(cx, *pos, id) {
-        Poll::Ready(Some((new_pos, x))) => {
-            debug_assert!(new_pos > *pos, "Must always grow {} > {}", new_pos, *pos);
-            let offset = new_pos - *pos - 1;
-            *pos = new_pos;
-            Poll::Ready(Some((offset, x)))
-        }
-        Poll::Ready(None) => {
-            *pos += 1;
-            Poll::Ready(None)
-        }
-        Poll::Pending => Poll::Pending,
+    /// Terminates this broadcast once all items before position `n` were received
+    ///
+    /// Position `n` is the `n`th item produced by the underlying stream, counting from 0.
+    /// If the items right before `n` were skipped, the broadcast terminates without receiving them.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(5);
+    /// broadcast.close_after(3);
+    /// assert_eq!(vec![(0, 0), (0, 1), (0, 2)], broadcast.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn close_after(&mut self, n: u64) {
+        self.close_after = Some(n);
     }
-}
 
-impl<T: FusedStream> FusedStream for StreamBroadcast<T>
-where
-    T::Item: Clone,
-{
-    fn is_terminated(&self) -> bool {
-        self.state.lock().unwrap().stream.is_terminated()
+    /// Changes the meaning of the `u64` in the items of this broadcast. Clones inherit the mode.
+    ///
+    /// Adapters, which count the skipped items themselves, e.g. [fallible](Self::fallible) and [relay](Self::relay),
+    /// always use [OffsetMode::PerItem].
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{OffsetMode, StreamBroadcastExt};
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(2);
+    /// let mut per_item = broadcast.clone();
+    /// let mut cumulative = broadcast.clone();
+    /// cumulative.set_offset_mode(OffsetMode::Cumulative);
+    ///
+    /// (&mut broadcast).take(5).count().await;
+    /// assert_eq!(Some((3, 3)), per_item.next().await);
+    /// assert_eq!(Some((3, 3)), cumulative.next().await);
+    ///
+    /// broadcast.count().await;
+    /// assert_eq!(vec![(4, 8), (0, 9)], per_item.collect::<Vec<_>>().await);
+    /// assert_eq!(vec![(7, 8), (7, 9)], cumulative.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn set_offset_mode(&mut self, mode: OffsetMode) {
+        self.offset_mode = mode;
     }
-}
 
-#[pin_project]
-struct StreamBroadcastState<T: FusedStream> {
+    /// Always jumps to the newest buffered item instead of replaying older ones. Clones don't inherit the mode.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(5);
+    /// let mut ui = broadcast.clone();
+    /// ui.conflate();
+    /// (&mut broadcast).take(4).count().await;
+    /// assert_eq!(Some((3, 3)), ui.next().await);
+    /// # }
+    /// ```
+    pub fn conflate(&mut self) {
+        self.conflate = true;
+    }
+
+    /// Reports skipped items as `Err(BroadcastError::Lagged { .. })` instead of the offset in the tuple
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{BroadcastError, StreamBroadcastExt};
+    ///
+    /// let broadcast = futures::stream::iter(0..4).fuse().broadcast(2);
+    /// let fallible = broadcast.clone().fallible();
+    /// assert_eq!(4, broadcast.count().await);
+    /// assert_eq!(
+    ///     vec![Err(BroadcastError::Lagged { skipped: 2 }), Ok(2), Ok(3)],
+    ///     fallible.collect::<Vec<_>>().await
+    /// );
+    /// # }
+    /// ```
+    pub fn fallible(self) -> FallibleBroadcast<T> {
+        self.into()
+    }
+
+    /// Returns a future, which polls the underlying stream until it terminates, independent of any subscriber
+    ///
+    /// Once the returned future was polled, broadcasts don't poll the underlying stream themselves anymore but only
+    /// read from the cache. Slow subscribers therefore never slow down the producer but skip items instead. If the
+    /// future is dropped before the stream terminated, the broadcasts poll the stream themselves again.
+    /// The returned future keeps the broadcast alive like a [StreamBroadcast] does, so dropping all other
+    /// broadcasts doesn't stop it. It yields to the executor after `size` items to not starve other tasks.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..5).fuse().broadcast(5);
+    /// broadcast.pump().await;
+    /// assert_eq!(5, broadcast.count().await);
+    /// # }
+    /// ```
+    pub fn pump(&self) -> impl std::future::Future<Output = ()> {
+        Pump::new(&self.state, true)
+    }
+
+    /// Creates a cursor which starts reading at the same position as a [clone](Clone::clone)
+    ///
+    /// In contrast to a clone, the cursor only borrows this broadcast. It doesn't keep the stream alive on its own,
+    /// but it's cheaper to create and can't outlive `self`. Each cursor tracks its own position, so many cursors
+    /// can read the same broadcast independently, e.g. in scoped tasks.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..3).fuse().broadcast(5);
+    /// let (a, b) = futures::join!(broadcast.cursor().count(), broadcast.cursor().count());
+    /// assert_eq!((3, 3), (a, b));
+    /// # }
+    /// ```
+    pub fn cursor(&self) -> BroadcastCursor<'_, T> {
+        BroadcastCursor::new(self)
+    }
+
+    /// Returns the position up to which both `self` and `other` received all items, or `None` if they don't share the same stream
+    ///
+    /// This is useful for at-least-once processing with redundant subscribers: everything before the returned
+    /// position was handled by both. If one of them fell behind the buffered window, the returned position is
+    /// still its own position, even though the items between it and the oldest buffered item will be skipped
+    /// (and reported as skipped) once it's polled again.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut a = futures::stream::iter(0..10).fuse().broadcast(5);
+    /// let mut b = a.clone();
+    /// (&mut a).take(4).count().await;
+    /// (&mut b).take(2).count().await;
+    /// assert_eq!(Some(2), a.merge_positions(&b));
+    /// # }
+    /// ```
+    pub fn merge_positions(&self, other: &Self) -> Option<u64> {
+        Arc::ptr_eq(&self.state, &other.state).then(|| self.pos.min(other.pos))
+    }
+
+    /// Calls `f` with every item entering the cache, exactly once and independent of the subscribers
+    ///
+    /// This is a side channel e.g. for audit logging. `f` is called in addition to previously registered functions,
+    /// while the shared state is locked, so it shouldn't block.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    /// broadcast.tap(move |x| tx.unbounded_send(*x).unwrap());
+    /// let lagging = broadcast.clone();
+    /// broadcast.count().await;
+    /// drop(lagging); // Drops the tap
+    /// assert_eq!(vec![0, 1, 2, 3, 4], rx.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn tap(&self, f: impl FnMut(&T::Item) + Send + 'static) {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().project().taps.push(Box::new(f));
+    }
+
+    /// Passes items to `f`, which are evicted from the cache before all subscribers received them
+    ///
+    /// This allows to persist or dead-letter items instead of losing them. Items are passed in the order they
+    /// were produced. Replaces a previously registered function.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// broadcast.set_overflow_sink(move |x| tx.unbounded_send(x).unwrap());
+    /// let lagging = broadcast.clone();
+    /// broadcast.count().await;
+    /// drop(lagging); // Drops the sink
+    /// assert_eq!(vec![0, 1], rx.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn set_overflow_sink(&self, f: impl FnMut(T::Item) + Send + 'static) {
+        let mut lock = lock_state(&self.state);
+        *lock.as_mut().project().overflow = Some(Box::new(f));
+    }
+
+    /// Calls `f` whenever a subscriber skips items, with its [id](Self::subscriber_id) and the number of skipped items
+    ///
+    /// Replaces a previously registered function. `f` is called while the shared state is locked.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// broadcast.set_on_lag(move |id, skipped| tx.unbounded_send((id, skipped)).unwrap());
+    /// let lagging = broadcast.clone();
+    /// let id = lagging.subscriber_id();
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// assert_eq!(3, lagging.count().await);
+    /// drop(broadcast); // Drops `f`
+    /// assert_eq!(vec![(id, 2)], rx.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn set_on_lag(&self, f: impl FnMut(SubscriberId, u64) + Send + 'static) {
+        let mut lock = lock_state(&self.state);
+        *lock.as_mut().project().on_lag = Some(Box::new(f));
+    }
+
+    /// Returns a stream of [SubscriberEvent]s, which allows a monitoring task to observe the subscribers
+    ///
+    /// The stream only yields events after this call and terminates once all handles of the broadcast are dropped.
+    /// Events are buffered without limit until they are received.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{StreamBroadcastExt, SubscriberEvent};
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// let events = broadcast.events();
+    /// let lagging = broadcast.clone();
+    /// let id = lagging.subscriber_id();
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// assert_eq!(3, lagging.count().await);
+    /// let first = broadcast.subscriber_id();
+    /// drop(broadcast); // Terminates `events`
+    /// assert_eq!(
+    ///     vec![
+    ///         SubscriberEvent::Joined(id),
+    ///         SubscriberEvent::Lagged { id, skipped: 2 },
+    ///         SubscriberEvent::Dropped(id),
+    ///         SubscriberEvent::Dropped(first),
+    ///     ],
+    ///     events.collect::<Vec<_>>().await
+    /// );
+    /// # }
+    /// ```
+    pub fn events(&self) -> UnboundedReceiver<SubscriberEvent> {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        lock_state(&self.state)
+            .as_mut()
+            .project()
+            .event_senders
+            .push(tx);
+        rx
+    }
+
+    /// Creates a clone, which starts reading at the first item ever produced by the underlying stream
+    ///
+    /// Items which are not buffered anymore are skipped. Use [retain_all](crate::BroadcastBuilder::retain_all)
+    /// to make sure that all items are still available.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast_builder().retain_all().build();
+    /// assert_eq!(3, (&mut broadcast).take(3).count().await);
+    /// let late = broadcast.resubscribe_from_start();
+    /// assert_eq!(vec![0, 1, 2, 3, 4], late.map(|(_, x)| x).collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn resubscribe_from_start(&self) -> Self {
+        let mut clone = Self::with_pos(self.state.clone(), 0);
+        clone.offset_mode = self.offset_mode;
+        clone
+    }
+
+    /// Creates a clone, which starts reading at `pos`
+    ///
+    /// `pos` is the position of the next item to receive, which is the sum of all offsets and items received so far.
+    /// It must be buffered or the position of the next item produced by the underlying stream.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// assert_eq!(5, (&mut broadcast).take(5).count().await);
+    /// let mut resumed = broadcast.subscribe_at(3).ok().unwrap();
+    /// assert_eq!(Some((0, 3)), resumed.next().await);
+    /// assert_eq!(2..=5, broadcast.subscribe_at(1).err().unwrap().available);
+    /// # }
+    /// ```
+    pub fn subscribe_at(&self, pos: u64) -> Result<Self, OutOfRange> {
+        let available = {
+            let mut lock = lock_state(&self.state);
+            lock.as_mut().expire();
+            lock.oldest_available()..=lock.global_pos
+        };
+        if !available.contains(&pos) {
+            return Err(OutOfRange { pos, available });
+        }
+        let mut clone = Self::with_pos(self.state.clone(), pos);
+        clone.offset_mode = self.offset_mode;
+        Ok(clone)
+    }
+
+    /// Returns the underlying stream, if this is the only [StreamBroadcast] and no [Sender](crate::Sender) exists
+    ///
+    /// Buffered items are dropped and [WeakStreamBroadcast]s terminate.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// let clone = broadcast.clone();
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// let broadcast = broadcast.into_inner().err().unwrap();
+    /// drop(clone);
+    /// let stream = broadcast.into_inner().ok().unwrap();
+    /// assert_eq!(vec![1, 2, 3, 4], stream.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn into_inner(self) -> Result<T, Self>
+    where
+        T: Unpin,
+    {
+        if Arc::strong_count(&self.state) != 1 {
+            return Err(self);
+        }
+        let state = self.state.clone();
+        let (pos, skipped_total, offset_mode, close_after, conflate) = (
+            self.pos,
+            self.skipped_total,
+            self.offset_mode,
+            self.close_after,
+            self.conflate,
+        );
+        drop(self);
+        match Arc::try_unwrap(state) {
+            Ok(state) => {
+                let state = state.into_inner().unwrap_or_else(|e| e.into_inner());
+                Ok(Pin::into_inner(state).stream)
+            }
+            // A weak broadcast upgraded in the meantime
+            Err(state) => {
+                let mut broadcast = Self::with_pos(state, pos);
+                broadcast.skipped_total = skipped_total;
+                broadcast.offset_mode = offset_mode;
+                broadcast.close_after = close_after;
+                broadcast.conflate = conflate;
+                Err(broadcast)
+            }
+        }
+    }
+
+    /// Keeps up to `limit` items for this broadcast, which are evicted from the shared cache before it received them
+    ///
+    /// This protects a single slow subscriber from skipping items without growing the cache for all others.
+    /// Only the newest `limit` evicted items are kept, older ones are skipped. A `limit` of 0 removes the backlog.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..6).fuse().broadcast(2);
+    /// let slow = broadcast.clone();
+    /// slow.set_backlog(3);
+    /// assert_eq!(6, (&mut broadcast).count().await);
+    /// assert_eq!(vec![(1, 1), (0, 2), (0, 3), (0, 4), (0, 5)], slow.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn set_backlog(&self, limit: usize) {
+        let mut lock = lock_state(&self.state);
+        let backlogs = lock.as_mut().project().backlogs;
+        if limit == 0 {
+            backlogs.remove(&self.id);
+            return;
+        }
+        let backlog = backlogs.entry(self.id).or_insert_with(|| Backlog {
+            items: Default::default(),
+            limit,
+        });
+        backlog.limit = limit;
+        while backlog.items.len() > limit {
+            backlog.items.pop_front();
+        }
+    }
+
+    /// Silently skips items, which are equal to the last item received by this broadcast
+    ///
+    /// Unlike [BroadcastBuilder::dedup], other broadcasts still receive the repeats. Skipped repeats are not
+    /// reported in the offset. Only used with [Delivery::Broadcast].
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter([1, 1, 2, 1, 1]).fuse().broadcast(5);
+    /// let all = broadcast.clone();
+    /// broadcast.distinct();
+    /// assert_eq!(vec![(0, 1), (0, 2), (0, 1)], broadcast.collect::<Vec<_>>().await);
+    /// assert_eq!(5, all.count().await);
+    /// # }
+    /// ```
+    pub fn distinct(&self)
+    where
+        T::Item: PartialEq + Send + 'static,
+    {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut()
+            .project()
+            .distinct
+            .insert(self.id, repeat_filter());
+    }
+
+    /// Stops receiving items until [resume](Self::resume) is called, without dropping this broadcast
+    ///
+    /// Polling a paused broadcast returns [Poll::Pending] and never polls the underlying stream.
+    /// The position is kept, so items are only skipped once they are evicted from the cache.
+    /// Paused broadcasts don't hold back the others with [LagPolicy::Backpressure].
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..3).fuse().broadcast_with_backpressure(1);
+    /// let mut paused = broadcast.clone();
+    /// paused.pause();
+    /// assert!(futures::poll!(paused.next()).is_pending());
+    /// assert_eq!(3, (&mut broadcast).count().await);
+    /// paused.resume();
+    /// assert_eq!(vec![(2, 2)], paused.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn pause(&self) {
+        let mut lock = lock_state(&self.state);
+        let this = lock.as_mut().project();
+        if let Some(subscriber) = this.subscribers.get_mut(&self.id) {
+            subscriber.paused = true;
+        }
+        this.rotation.retain(|id| *id != self.id);
+    }
+
+    /// Continues receiving items at the position where [pause](Self::pause) stopped
+    pub fn resume(&self) {
+        let mut lock = lock_state(&self.state);
+        let this = lock.as_mut().project();
+        let paused = this
+            .subscribers
+            .get_mut(&self.id)
+            .is_some_and(|subscriber| std::mem::replace(&mut subscriber.paused, false));
+        if paused {
+            lock.as_mut().join_rotation(self.id);
+        }
+        let this = lock.as_mut().project();
+        if let Some(waker) = this.wakable.remove(&self.id) {
+            waker.wake();
+        }
+    }
+
+    /// Wakes this broadcast before all broadcasts with a lower `priority`, when a new item is available
+    ///
+    /// Broadcasts start with the priority passed to [BroadcastBuilder::priority], which defaults to 0.
+    /// Latency-critical consumers should get a higher priority than best-effort consumers.
+    /// Subscribers of the same priority are woken in arbitrary order.
+    pub fn set_priority(&self, priority: i32) {
+        let mut lock = lock_state(&self.state);
+        let this = lock.as_mut().project();
+        if let Some(subscriber) = this.subscribers.get_mut(&self.id) {
+            let default = *this.default_priority;
+            *this.prioritized -= usize::from(subscriber.priority != default);
+            *this.prioritized += usize::from(priority != default);
+            subscriber.priority = priority;
+        }
+    }
+
+    /// Whether [pause](Self::pause) was called without [resume](Self::resume)
+    pub fn is_paused(&self) -> bool {
+        lock_state(&self.state)
+            .subscribers
+            .get(&self.id)
+            .is_some_and(|s| s.paused)
+    }
+
+    /// Stops polling the underlying stream for all broadcasts
+    ///
+    /// The broadcasts receive the items which are still buffered and terminate afterwards.
+    /// The underlying stream is not polled anymore, but only dropped with the last broadcast.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..).fuse().broadcast(5);
+    /// let lagging = broadcast.clone();
+    /// assert_eq!(3, (&mut broadcast).take(3).count().await);
+    /// broadcast.close();
+    /// assert_eq!(None, broadcast.next().await);
+    /// assert_eq!(vec![0, 1, 2], lagging.map(|(_, x)| x).collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn close(&self) {
+        lock_state(&self.state).as_mut().close(self.id);
+    }
+
+    /// Changes the maximum number of buffered items for all broadcasts of the underlying stream
+    ///
+    /// Buffered items are kept. When shrinking, the oldest items which don't fit anymore are evicted
+    /// and passed to the [overflow sink](Self::set_overflow_sink).
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(2);
+    /// let lagging = broadcast.clone();
+    /// broadcast.set_capacity(5);
+    /// assert_eq!(6, (&mut broadcast).take(6).count().await);
+    /// broadcast.set_capacity(3);
+    /// assert_eq!(vec![(3, 3), (0, 4), (0, 5)], lagging.take(3).collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().resize(capacity);
+        lock.as_mut().wake_all(self.id);
+    }
+
+    /// Returns statistics about the cache utilization and the subscribers, which help to choose an appropriate `size`
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let lagging = broadcast.clone();
+    /// broadcast.count().await;
+    /// let stats = lagging.stats();
+    /// assert_eq!(3, stats.capacity);
+    /// assert_eq!(3, stats.current_len);
+    /// assert_eq!(10, stats.max_lag_observed);
+    /// assert_eq!(10, stats.items_produced);
+    /// assert_eq!(7, stats.items_dropped_unseen);
+    /// assert_eq!((1, 0), (stats.strong_count, stats.weak_count));
+    /// assert_eq!(Some(0), stats.min_subscriber_pos);
+    /// # }
+    /// ```
+    pub fn stats(&self) -> BroadcastStats {
+        lock_state(&self.state).stats()
+    }
+
+    /// Number of items currently buffered
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// (&mut broadcast).take(2).count().await;
+    /// assert_eq!(2, broadcast.buffered_len());
+    /// assert_eq!(3, broadcast.capacity());
+    /// assert_eq!(0, broadcast.oldest_available_pos());
+    /// (&mut broadcast).take(2).count().await;
+    /// assert_eq!(3, broadcast.buffered_len());
+    /// assert_eq!(1, broadcast.oldest_available_pos());
+    /// # }
+    /// ```
+    pub fn buffered_len(&self) -> usize {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        lock.len
+    }
+
+    /// Maximum number of buffered items
+    pub fn capacity(&self) -> usize {
+        lock_state(&self.state).cache.capacity()
+    }
+
+    /// Number of items produced by the underlying stream, which this broadcast didn't receive yet
+    ///
+    /// Items are skipped once the lag exceeds the [capacity](Self::capacity).
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let slow = broadcast.clone();
+    /// (&mut broadcast).take(2).count().await;
+    /// assert_eq!(0, broadcast.lag());
+    /// assert_eq!(2, slow.lag());
+    /// # }
+    /// ```
+    pub fn lag(&self) -> u64 {
+        let global_pos = lock_state(&self.state).global_pos;
+        global_pos.saturating_sub(self.pos)
+    }
+
+    /// Number of items this broadcast skipped since it was created, independent of the [OffsetMode]
+    ///
+    /// Clones start counting at 0.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let mut slow = broadcast.clone();
+    /// (&mut broadcast).take(5).count().await;
+    /// assert_eq!(Some((2, 2)), slow.next().await);
+    /// broadcast.count().await;
+    /// assert_eq!(Some((4, 7)), slow.next().await);
+    /// assert_eq!(6, slow.skipped_total());
+    /// assert_eq!(0, slow.clone().skipped_total());
+    /// # }
+    /// ```
+    pub fn skipped_total(&self) -> u64 {
+        self.skipped_total
+    }
+
+    /// Identifies this broadcast in [BroadcastMetrics]
+    pub fn subscriber_id(&self) -> SubscriberId {
+        SubscriberId(self.id)
+    }
+
+    /// Position of the next item to receive, which is the sum of all offsets and items received so far
+    ///
+    /// Can be persisted to resume with [subscribe_at](Self::subscribe_at) later on.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// assert_eq!(4, (&mut broadcast).take(4).count().await);
+    /// let resumed = broadcast.subscribe_at(broadcast.position()).ok().unwrap();
+    /// assert_eq!(vec![4, 5], resumed.take(2).map(|(_, x)| x).collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Number of [StreamBroadcast]s and [cursors](Self::cursor) reading the underlying stream, including `self`
+    ///
+    /// ```
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let clone = broadcast.clone();
+    /// let weak = broadcast.downgrade();
+    /// assert_eq!(2, broadcast.subscriber_count());
+    /// assert_eq!(1, broadcast.weak_count());
+    /// drop(clone);
+    /// drop(weak);
+    /// assert_eq!(1, broadcast.subscriber_count());
+    /// assert_eq!(0, broadcast.weak_count());
+    /// ```
+    pub fn subscriber_count(&self) -> usize {
+        lock_state(&self.state).subscriber_count()
+    }
+
+    /// Waits until at least `n` broadcasts, including this one, read the underlying stream
+    ///
+    /// Use [min_subscribers](crate::BroadcastBuilder::min_subscribers) to hold back the underlying stream as well.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..3).fuse().broadcast(3);
+    /// let consumer = broadcast.clone();
+    /// let joined = tokio::spawn(async move {
+    ///     consumer.wait_for_subscribers(3).await;
+    ///     consumer.count().await
+    /// });
+    /// let late = broadcast.clone();
+    /// assert_eq!(3, joined.await.unwrap());
+    /// assert_eq!(3, late.count().await);
+    /// # }
+    /// ```
+    pub async fn wait_for_subscribers(&self, n: usize) {
+        let id = create_id();
+        let _joined = JoinedWaker {
+            id,
+            state: &self.state,
+        };
+        futures::future::poll_fn(|cx| {
+            let mut lock = lock_state(&self.state);
+            if lock.subscriber_count() >= n {
+                return Poll::Ready(());
+            }
+            lock.as_mut()
+                .project()
+                .joined
+                .insert(id, cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Number of [WeakStreamBroadcast]s reading the underlying stream
+    pub fn weak_count(&self) -> usize {
+        let lock = lock_state(&self.state);
+        lock.subscribers.values().filter(|s| !s.strong).count()
+    }
+
+    /// Position of the oldest item, which is still buffered
+    ///
+    /// Positions count the items produced by the underlying stream, starting at 0.
+    /// If nothing is buffered, this is the position of the next item.
+    pub fn oldest_available_pos(&self) -> u64 {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        lock.oldest_available()
+    }
+
+    /// Creates a clone, which only yields the items matching `predicate`
+    ///
+    /// `predicate` runs on the buffered item while the shared state is locked, so only matching items are cloned.
+    /// Keep it short and don't access any broadcast of the same stream from within `predicate`, as this would deadlock.
+    /// Offsets only count items skipped because the broadcast fell behind, not the ones filtered out.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(0..10).fuse().broadcast(5);
+    /// let even = broadcast.subscribe_filter(|x| x % 2 == 0);
+    /// assert_eq!(10, broadcast.count().await);
+    /// assert_eq!(vec![(5, 6), (0, 8)], even.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn subscribe_filter<F>(&self, predicate: F) -> FilteredBroadcast<T, F>
+    where
+        F: FnMut(&T::Item) -> bool,
+    {
+        FilteredBroadcast::new(self.clone(), predicate)
+    }
+
+    /// Creates a clone, which yields the result of `projection` instead of cloning the whole item
+    ///
+    /// `projection` runs on the buffered item while the shared state is locked. Keep it short and don't access
+    /// any broadcast of the same stream from within `projection`, as this would deadlock.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter(["a", "bb", "ccc"]).fuse().broadcast(5);
+    /// let lengths = broadcast.subscribe_with(|x| x.len());
+    /// assert_eq!(3, broadcast.count().await);
+    /// assert_eq!(vec![(0, 1), (0, 2), (0, 3)], lengths.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn subscribe_with<F, U>(&self, projection: F) -> MappedBroadcast<T, F>
+    where
+        F: FnMut(&T::Item) -> U,
+    {
+        MappedBroadcast::new(self.clone(), projection)
+    }
+
+    /// Yields the items without the number of skipped items, see [broadcast_items](StreamBroadcastExt::broadcast_items)
+    pub fn items(self) -> ItemBroadcast<T> {
+        self.into()
+    }
+
+    /// Yields [BroadcastEvent]s, so lagging and termination can be handled with a single `match`
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{BroadcastEvent, StreamBroadcastExt};
+    ///
+    /// let broadcast = futures::stream::iter(0..4).fuse().broadcast(2);
+    /// let mut events = broadcast.clone().into_events();
+    /// assert_eq!(4, broadcast.count().await);
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         BroadcastEvent::Item(x) => assert!(x >= 2),
+    ///         BroadcastEvent::Lagged(skipped) => assert_eq!(2, skipped),
+    ///         BroadcastEvent::Closed => break,
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn into_events(self) -> EventBroadcast<T> {
+        self.into()
+    }
+
+    /// Fetches the items from `backfill`, which this broadcast would skip otherwise
+    ///
+    /// Instead of skipping evicted items, the broadcast waits for `backfill` to fetch them, e.g. from a database.
+    /// Items missing in the fetched ones are still reported as skipped. Requires [OffsetMode::PerItem]
+    /// and shouldn't be combined with [conflate](Self::conflate), whose skipped items would be fetched as well.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::{future::Ready, StreamExt};
+    /// use stream_broadcast::{Backfill, StreamBroadcastExt};
+    /// use std::ops::Range;
+    ///
+    /// struct Store;
+    /// impl Backfill<u64> for Store {
+    ///     type Fetch = Ready<Vec<u64>>;
+    ///     fn fetch(&mut self, range: Range<u64>) -> Self::Fetch {
+    ///         futures::future::ready(range.collect())
+    ///     }
+    /// }
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5u64).fuse().broadcast(2);
+    /// let lagging = broadcast.clone().with_backfill(Store);
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// assert_eq!((0..5).map(|x| (0, x)).collect::<Vec<_>>(), lagging.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn with_backfill<B: Backfill<T::Item>>(self, backfill: B) -> BackfillBroadcast<T, B> {
+        BackfillBroadcast::new(self, backfill)
+    }
+
+    /// Yields at most one item per `interval`, waiting with the futures created by `sleep`
+    ///
+    /// The items aren't read from the cache during the delay, so a throttled broadcast lags and skips
+    /// like a slow consumer instead of buffering items downstream. Combine it with [conflate](Self::conflate)
+    /// to always receive the newest item after the delay.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::time::Duration;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let mut throttled = broadcast.clone();
+    /// throttled.conflate();
+    /// let mut throttled = throttled.throttle(Duration::from_millis(10), tokio::time::sleep);
+    /// assert_eq!(Some((0, 0)), throttled.next().await);
+    /// assert_eq!(10, (&mut broadcast).count().await);
+    /// assert_eq!(Some((8, 9)), throttled.next().await);
+    /// # }
+    /// ```
+    pub fn throttle<S, F>(
+        self,
+        interval: std::time::Duration,
+        sleep: S,
+    ) -> ThrottledBroadcast<T, S, F>
+    where
+        S: FnMut(std::time::Duration) -> F,
+        F: std::future::Future<Output = ()>,
+    {
+        ThrottledBroadcast::new(self, interval, sleep)
+    }
+
+    /// Yields [TimeoutEvent::Timeout] whenever no item arrived for `duration`, e.g. to show stale data
+    ///
+    /// The timer starts on the first poll and restarts after every item and every timeout, so a silent underlying
+    /// stream produces a timeout every `duration`.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{StreamBroadcastExt, TimeoutEvent};
+    /// use std::time::Duration;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let mut events = rx.broadcast(3).inactivity_timeout(Duration::from_millis(10));
+    /// tx.unbounded_send(1).unwrap();
+    /// assert_eq!(Some(TimeoutEvent::Item(0, 1)), events.next().await);
+    /// assert_eq!(Some(TimeoutEvent::Timeout), events.next().await);
+    /// drop(tx);
+    /// assert_eq!(None, events.next().await);
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn inactivity_timeout(self, duration: std::time::Duration) -> TimeoutBroadcast<T> {
+        TimeoutBroadcast::new(self, duration)
+    }
+
+    /// Receives up to `max` items at once, which saves a lock round trip per item when this broadcast fell behind
+    ///
+    /// Waits for the first item like [next](futures::StreamExt::next). Further items are only added if they are
+    /// already buffered. Returns `None` if the broadcast terminated before the first item.
+    ///
+    /// # Panics
+    /// Panics if `max` is 0
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// let mut slow = broadcast.clone();
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// assert_eq!(Some(vec![(2, 2), (0, 3)]), slow.next_batch(2).await);
+    /// assert_eq!(Some(vec![(0, 4)]), slow.next_batch(2).await);
+    /// assert_eq!(None, slow.next_batch(2).await);
+    /// # }
+    /// ```
+    pub fn next_batch(
+        &mut self,
+        max: usize,
+    ) -> impl std::future::Future<Output = Option<Batch<T::Item>>> + '_ {
+        assert!(max > 0, "max must be greater than 0");
+        futures::future::poll_fn(move |cx| self.poll_next_batch(cx, max))
+    }
+
+    /// Polls up to `max` items at once, see [next_batch](Self::next_batch)
+    ///
+    /// # Panics
+    /// Panics if `max` is 0
+    pub fn poll_next_batch(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        max: usize,
+    ) -> Poll<Option<Batch<T::Item>>> {
+        assert!(max > 0, "max must be greater than 0");
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        let mut batch = Vec::new();
+        while batch.len() < max && !lock.is_beyond(self.pos, self.close_after) {
+            let skipped = if self.conflate {
+                lock.as_mut()
+                    .fast_forward(self.id, &mut self.pos, self.close_after)
+            } else {
+                0
+            };
+            let (request_pos, _) = lock.read_pos(self.id, self.pos);
+            if !batch.is_empty() && lock.next_filled(request_pos).is_none() {
+                break;
+            }
+            match broadast_next(lock.deref_mut().as_mut(), cx, &mut self.pos, self.id) {
+                Poll::Ready(Some((offset, x))) => {
+                    let offset = self
+                        .offset_mode
+                        .report(&mut self.skipped_total, skipped + offset);
+                    batch.push((offset, x));
+                }
+                Poll::Ready(None) => break,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        drop(lock);
+        if batch.is_empty() {
+            return Poll::Ready(None);
+        }
+        let batch = batch
+            .into_iter()
+            .map(|(offset, x)| (offset, x.into_owned()))
+            .collect();
+        Poll::Ready(Some(batch))
+    }
+
+    /// Returns the next item if it is already buffered, without polling the underlying stream.
+    ///
+    /// Unlike [poll_next](Stream::poll_next), this never registers a waker and never drives the
+    /// inner stream, so it is safe to call from synchronous code (e.g. to drain the buffer in a game loop).
+    /// `None` means that nothing is buffered for this broadcast right now, `Some(None)` that the broadcast terminated.
+    ///
+    /// The underlying stream isn't polled, because polling it without a waker would replace the waker
+    /// of a subscriber waiting for the next item.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..1).fuse().broadcast(5);
+    /// let mut broadcast2 = broadcast.clone();
+    /// assert_eq!(None, broadcast2.try_next_now());
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// assert_eq!(Some(Some((0, 0))), broadcast2.try_next_now());
+    /// assert_eq!(None, broadcast.next().await);
+    /// assert_eq!(Some(None), broadcast2.try_next_now());
+    /// # }
+    /// ```
+    pub fn try_next_now(&mut self) -> Option<Option<(u64, T::Item)>> {
+        let mut lock = lock_state(&self.state);
+        let read = try_next_cached(
+            lock.as_mut(),
+            &mut self.pos,
+            self.id,
+            self.close_after,
+            self.conflate,
+        );
+        drop(lock);
+        Some(read?.map(|(offset, x)| {
+            (
+                self.offset_mode.report(&mut self.skipped_total, offset),
+                x.into_owned(),
+            )
+        }))
+    }
+
+    /// Waits for the next item like [next](futures::StreamExt::next), but doesn't consume it
+    ///
+    /// The next call to [next](futures::StreamExt::next) returns the same item, unless it was evicted in the
+    /// meantime. Other broadcasts are unaffected. See [poll_peek](Self::poll_peek).
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..2).fuse().broadcast(5);
+    /// assert_eq!(Some((0, 0)), broadcast.peek().await);
+    /// assert_eq!(Some((0, 0)), broadcast.peek().await);
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// assert_eq!(Some((0, 1)), broadcast.peek().await);
+    /// assert_eq!(Some((0, 1)), broadcast.next().await);
+    /// assert_eq!(None, broadcast.peek().await);
+    /// # }
+    /// ```
+    pub fn peek(&mut self) -> impl std::future::Future<Output = Option<(u64, T::Item)>> + '_ {
+        futures::future::poll_fn(move |cx| self.poll_peek(cx))
+    }
+
+    /// Polls the next item without consuming it, see [peek](Self::peek)
+    ///
+    /// The underlying stream is polled like by [poll_next](Stream::poll_next), if nothing is buffered for this
+    /// broadcast. Repeats, which [distinct](Self::distinct) skips, are peeked nevertheless.
+    pub fn poll_peek(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Option<(u64, T::Item)>> {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        if lock.is_beyond(self.pos, self.close_after) {
+            return Poll::Ready(None);
+        }
+        let pos = if self.conflate {
+            lock.conflated_pos(self.pos, self.close_after)
+        } else {
+            self.pos
+        };
+        let polled = lock.as_mut().peek(cx, pos, self.id, Share);
+        drop(lock);
+        let mut skipped_total = self.skipped_total;
+        polled.map(|x| {
+            x.map(|(offset, _, x)| {
+                let offset = pos - self.pos + offset;
+                (
+                    self.offset_mode.report(&mut skipped_total, offset),
+                    x.into_owned(),
+                )
+            })
+        })
+    }
+
+    /// Receives the next item, only if `predicate` accepts it. Otherwise, the item is left for the next call.
+    ///
+    /// Waits for the next item like [peek](Self::peek), but checks and consumes it while the shared state is
+    /// locked, so no other broadcast can claim it in between, e.g. with [Delivery::Distribute]. Like
+    /// [Iterator::peekable], `None` is returned for a rejected item and after the broadcast terminated.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(["ping", "data"]).fuse().broadcast(5);
+    /// assert_eq!(Some((0, "ping")), broadcast.next_if(|x| *x == "ping").await);
+    /// assert_eq!(None, broadcast.next_if(|x| *x == "ping").await);
+    /// assert_eq!(Some((0, "data")), broadcast.next().await);
+    /// # }
+    /// ```
+    pub fn next_if<'a>(
+        &'a mut self,
+        mut predicate: impl FnMut(&T::Item) -> bool + 'a,
+    ) -> impl std::future::Future<Output = Option<(u64, T::Item)>> + 'a {
+        futures::future::poll_fn(move |cx| self.poll_next_if(cx, &mut predicate))
+    }
+
+    /// Polls the next item, only if `predicate` accepts it, see [next_if](Self::next_if)
+    pub fn poll_next_if(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        predicate: impl FnOnce(&T::Item) -> bool,
+    ) -> Poll<Option<(u64, T::Item)>> {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        if lock.is_beyond(self.pos, self.close_after) {
+            return Poll::Ready(None);
+        }
+        let pos = if self.conflate {
+            lock.conflated_pos(self.pos, self.close_after)
+        } else {
+            self.pos
+        };
+        let accepted = futures::ready!(lock.as_mut().peek(cx, pos, self.id, predicate));
+        if !matches!(accepted, Some((_, _, true))) {
+            return Poll::Ready(None);
+        }
+        let skipped = if self.conflate {
+            lock.as_mut()
+                .fast_forward(self.id, &mut self.pos, self.close_after)
+        } else {
+            0
+        };
+        let polled = broadast_next(lock.deref_mut().as_mut(), cx, &mut self.pos, self.id);
+        drop(lock);
+        into_owned(polled).map(|x| {
+            x.map(|(offset, x)| {
+                let offset = skipped + offset;
+                (self.offset_mode.report(&mut self.skipped_total, offset), x)
+            })
+        })
+    }
+
+    /// Calls `f` with a reference to the most recently buffered item, avoiding a clone
+    ///
+    /// `f` runs while the shared state is locked, which blocks all other broadcasts of the same stream.
+    /// Keep it short and don't access any broadcast of the same stream from within `f`, as this would deadlock.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(["a", "b"]).fuse().broadcast(5);
+    /// assert_eq!(None, broadcast.with_latest(|x| x.map(|x| x.len())));
+    /// broadcast.next().await;
+    /// assert_eq!(Some("a"), broadcast.with_latest(|x| x.copied()));
+    /// # }
+    /// ```
+    pub fn with_latest<R>(&self, f: impl FnOnce(Option<&T::Item>) -> R) -> R {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        f(lock.latest())
+    }
+
+    /// Copies all buffered items with their position, from oldest to newest, e.g. to show the recent activity
+    ///
+    /// The items are cloned while the shared state is locked. Positions of items lost by the underlying stream
+    /// are left out.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// (&mut broadcast).count().await;
+    /// assert_eq!(vec![(2, 2), (3, 3), (4, 4)], broadcast.buffer_snapshot());
+    /// # }
+    /// ```
+    pub fn buffer_snapshot(&self) -> Vec<(u64, T::Item)> {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        lock.cached().map(|(pos, x)| (pos, x.clone())).collect()
+    }
+
+    /// Waits for the next item for at most `dur`
+    ///
+    /// On timeout, no item is consumed, so a subsequent call continues exactly where this one stopped.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::time::Duration;
+    ///
+    /// let mut broadcast = futures::stream::pending::<i32>().fuse().broadcast(5);
+    /// assert!(broadcast.next_timeout(Duration::from_millis(1)).await.is_err());
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn next_timeout(
+        &mut self,
+        dur: std::time::Duration,
+    ) -> Result<Option<(u64, T::Item)>, tokio::time::error::Elapsed> {
+        let result = tokio::time::timeout(dur, futures::StreamExt::next(self)).await;
+        if result.is_err() {
+            // Don't leave the waker of the dropped future behind
+            let mut lock = lock_state(&self.state);
+            lock.as_mut().remove_waker(self.id);
+        }
+        result
+    }
+}
+
+impl<T: Stream> Stream for StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut lock = lock_state(&this.state);
+        lock.as_mut().expire();
+        if lock.is_beyond(this.pos, this.close_after) {
+            return Poll::Ready(None);
+        }
+        let skipped = if this.conflate {
+            lock.as_mut()
+                .fast_forward(this.id, &mut this.pos, this.close_after)
+        } else {
+            0
+        };
+        let polled = broadast_next(lock.deref_mut().as_mut(), cx, &mut this.pos, this.id);
+        // An expensive clone doesn't block the other subscribers
+        drop(lock);
+        into_owned(polled).map(|x| {
+            x.map(|(offset, x)| {
+                let offset = skipped + offset;
+                (this.offset_mode.report(&mut this.skipped_total, offset), x)
+            })
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = lock_state(&self.state).size_hint(self.id, self.pos, self.close_after);
+        if self.conflate {
+            (lower.min(1), upper)
+        } else {
+            (lower, upper)
+        }
+    }
+}
+fn create_id() -> u64 {
+    static ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+    ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+}
+fn broadast_next<T: Stream>(
+    mut pinned: Pin<&mut StreamBroadcastState<T>>,
+    cx: &mut std::task::Context<'_>,
+    pos: &mut u64,
+    id: u64,
+) -> Poll<Option<(u64, Slot<T::Item>)>>
+where
+    T::Item: Clone,
+{
+    match pinned.as_mut().poll(cx, *pos, id) {
+        Poll::Ready(Some((offset, new_pos, x))) => {
+            // The seed is received without moving to the next position
+            debug_assert!(new_pos >= *pos, "Must never shrink {} >= {}", new_pos, *pos);
+            *pos = new_pos;
+            pinned.advance(id, new_pos, offset);
+            Poll::Ready(Some((offset, x)))
+        }
+        Poll::Ready(None) => {
+            *pos += 1;
+            Poll::Ready(None)
+        }
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+/// Clones the item returned by [broadast_next], after the shared state was unlocked
+fn into_owned<I: Clone>(polled: Poll<Option<(u64, Slot<I>)>>) -> Poll<Option<(u64, I)>> {
+    polled.map(|x| x.map(|(offset, x)| (offset, x.into_owned())))
+}
+
+/// Implements [StreamBroadcast::try_next_now] and [LocalStreamBroadcast::try_next_now]
+///
+/// Returns `None` if the item at `pos` isn't buffered yet. The offset isn't passed through an [OffsetMode].
+fn try_next_cached<T: Stream>(
+    mut pinned: Pin<&mut StreamBroadcastState<T>>,
+    pos: &mut u64,
+    id: u64,
+    close_after: Option<u64>,
+    conflate: bool,
+) -> Option<Option<(u64, Slot<T::Item>)>>
+where
+    T::Item: Clone,
+{
+    pinned.as_mut().expire();
+    if pinned.is_beyond(*pos, close_after) {
+        return Some(None);
+    }
+    let (mut request_pos, mut skipped) = pinned.read_pos(id, *pos);
+    if conflate {
+        skipped += pinned
+            .as_mut()
+            .fast_forward(id, &mut request_pos, close_after);
+    }
+    let Some((new_pos, x)) = pinned.poll_cached(request_pos) else {
+        return pinned.is_closed().then_some(None);
+    };
+    let offset = new_pos - request_pos - 1;
+    *pos = new_pos;
+    pinned.as_mut().advance(id, new_pos, offset);
+    Some(Some((skipped + offset, x)))
+}
+
+/// Like [broadast_next], but passes the items to `read` by reference and continues while it returns `None`
+///
+/// `skipped` accumulates the items skipped since the last returned item, even across `Poll::Pending`.
+fn broadcast_next_with<T: Stream, R>(
+    mut pinned: Pin<&mut StreamBroadcastState<T>>,
+    cx: &mut std::task::Context<'_>,
+    pos: &mut u64,
+    id: u64,
+    skipped: &mut u64,
+    read: &mut impl FnMut(&T::Item) -> Option<R>,
+) -> Poll<Option<(u64, R)>>
+where
+    T::Item: Clone,
+{
+    loop {
+        match pinned.as_mut().poll_with(cx, *pos, id, &mut *read) {
+            Poll::Ready(Some((offset, new_pos, x))) => {
+                *pos = new_pos;
+                pinned.as_mut().advance(id, new_pos, offset);
+                *skipped += offset;
+                if let Some(x) = x {
+                    return Poll::Ready(Some((std::mem::take(skipped), x)));
+                }
+            }
+            Poll::Ready(None) => {
+                *pos += 1;
+                return Poll::Ready(None);
+            }
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+impl<T: Stream> FusedStream for StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        let lock = lock_state(&self.state);
+        lock.is_closed() || lock.is_beyond(self.pos, self.close_after)
+    }
+}
+
+type SharedState<T> = Arc<Mutex<Pin<Box<StreamBroadcastState<T>>>>>;
+type StateGuard<'a, T> = MutexGuard<'a, Pin<Box<StreamBroadcastState<T>>>>;
+
+/// Locks the shared state and handles a poisoned lock according to its [PoisonPolicy]
+///
+/// # Panics
+/// Panics if the lock is poisoned and the policy is [PoisonPolicy::Propagate]
+fn lock_state<T: Stream>(state: &Mutex<Pin<Box<StreamBroadcastState<T>>>>) -> StateGuard<'_, T> {
+    try_lock_state(state).expect("A panic poisoned the shared state of the broadcast")
+}
+
+/// Like [lock_state], but returns `None` instead of panicking
+fn try_lock_state<T: Stream>(
+    state: &Mutex<Pin<Box<StreamBroadcastState<T>>>>,
+) -> Option<StateGuard<'_, T>> {
+    match state.lock() {
+        Ok(lock) => Some(lock),
+        Err(poisoned) => {
+            let mut lock = poisoned.into_inner();
+            match lock.poison_policy {
+                PoisonPolicy::Propagate => return None,
+                PoisonPolicy::Terminate => lock.as_mut().terminate(),
+                PoisonPolicy::Recover => {}
+            }
+            state.clear_poison();
+            Some(lock)
+        }
+    }
+}
+
+/// Removes the waker of [wait_for_subscribers](StreamBroadcast::wait_for_subscribers), if the future is dropped
+/// before enough subscribers joined
+struct JoinedWaker<'a, T: Stream> {
+    id: u64,
+    state: &'a Mutex<Pin<Box<StreamBroadcastState<T>>>>,
+}
+
+impl<T: Stream> Drop for JoinedWaker<'_, T> {
+    fn drop(&mut self) {
+        if let Some(mut lock) = try_lock_state(self.state) {
+            lock.as_mut().project().joined.remove(&self.id);
+        }
+    }
+}
+
+/// Future of [StreamBroadcast::pump] and the task of [broadcast_driven](StreamBroadcastExt::broadcast_driven)
+///
+/// Subscribers stop polling the underlying stream once it's polled the first time. If it's dropped before the
+/// stream terminated, e.g. because the task was aborted, they are woken to poll the stream themselves again.
+struct Pump<T: Stream> {
+    id: u64,
+    state: std::sync::Weak<Mutex<Pin<Box<StreamBroadcastState<T>>>>>,
+    /// Keeps the broadcast alive for [StreamBroadcast::pump]
+    _keep_alive: Option<SharedState<T>>,
+    started: bool,
+}
+
+impl<T: Stream> Pump<T> {
+    fn new(state: &SharedState<T>, keep_alive: bool) -> Self {
+        Self {
+            id: create_id(),
+            state: Arc::downgrade(state),
+            _keep_alive: keep_alive.then(|| state.clone()),
+            started: false,
+        }
+    }
+}
+
+impl<T: Stream> std::future::Future for Pump<T>
+where
+    T::Item: Clone,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        let Some(state) = this.state.upgrade() else {
+            return Poll::Ready(());
+        };
+        let mut lock = lock_state(&state);
+        if !this.started {
+            this.started = true;
+            *lock.as_mut().project().pumped = true;
+        }
+        lock.as_mut().poll_pump(cx, this.id)
+    }
+}
+
+impl<T: Stream> Drop for Pump<T> {
+    fn drop(&mut self) {
+        let Some(state) = self.state.upgrade().filter(|_| self.started) else {
+            return;
+        };
+        let Some(mut lock) = try_lock_state(&state) else {
+            return;
+        };
+        let this = lock.as_mut().project();
+        *this.pumped = false;
+        this.wakable.remove(&self.id);
+        this.joined.remove(&self.id);
+        lock.as_mut().wake_all(self.id);
+    }
+}
+
+type InspectFn<I> = Box<dyn FnMut(&I) + Send>;
+type OverflowFn<I> = Box<dyn FnMut(I) + Send>;
+type LagFn = Box<dyn FnMut(SubscriberId, u64) + Send>;
+/// Returns whether an item equals the previous one passed to it
+type DedupFn<I> = Box<dyn FnMut(&I) -> bool + Send>;
+type CheckpointFn<I> = Box<dyn FnMut(u64, &I) -> bool + Send>;
+
+/// Sends `event` to the receivers of [StreamBroadcast::events] and forgets the dropped ones
+fn emit(senders: &mut Vec<UnboundedSender<SubscriberEvent>>, event: SubscriberEvent) {
+    senders.retain(|sender| sender.unbounded_send(event).is_ok());
+}
+
+fn repeat_filter<I: PartialEq + Clone + Send + 'static>() -> DedupFn<I> {
+    let mut previous = None;
+    Box::new(move |x| {
+        let repeat = previous.as_ref() == Some(x);
+        if !repeat {
+            previous = Some(x.clone());
+        }
+        repeat
+    })
+}
+type Batch<I> = Vec<(u64, I)>;
+/// Result of [StreamBroadcastState::poll]
+type Polled<I> = Poll<Option<(u64, u64, I)>>;
+
+/// Bounds the cache by the sum of `weigh` over all buffered items
+struct Weigher<I> {
+    max: usize,
+    weigh: Box<dyn Fn(&I) -> usize + Send>,
+}
+
+#[pin_project]
+struct StreamBroadcastState<T: Stream> {
     #[pin]
     stream: T,
     global_pos: u64,
-    cache: Vec<T::Item>,
-    wakable: Vec<(u64, std::task::Waker)>,
+    /// Buffered items, which is a [RingStorage] unless set by [BroadcastBuilder::storage]
+    cache: Storage<T::Item>,
+    /// Number of filled slots, which are the positions `global_pos - len..global_pos`
+    len: usize,
+    /// Waker of each subscriber waiting for the next item, by id
+    wakable: HashMap<u64, std::task::Waker>,
+    checkpoints: Option<Checkpoints<T::Item>>,
+    /// Set by [BroadcastBuilder::seed]
+    seed: Option<T::Item>,
+    /// Set by [BroadcastBuilder::dedup]
+    dedup: Option<DedupFn<T::Item>>,
+    /// Selects the items, which are only received by the polling subscriber, set by
+    /// [BroadcastBuilder::error_delivery]
+    is_private: Option<fn(&T::Item) -> bool>,
+    /// Items selected by `is_private`, by the id of the subscriber whose poll produced them
+    private: HashMap<u64, T::Item>,
+    /// Called with every item pushed into the cache, registered by [StreamBroadcast::tap]
+    taps: Vec<InspectFn<T::Item>>,
+    overflow: Option<OverflowFn<T::Item>>,
+    /// Set by [BroadcastBuilder::on_evict]
+    on_evict: Option<OverflowFn<T::Item>>,
+    metrics: Option<Box<dyn BroadcastMetrics>>,
+    on_lag: Option<LagFn>,
+    /// Receivers returned by [StreamBroadcast::events]
+    event_senders: Vec<UnboundedSender<SubscriberEvent>>,
+    /// Set by [BroadcastBuilder::min_subscribers] and reset to 0 once reached
+    min_subscribers: usize,
+    /// Wakers of [StreamBroadcast::wait_for_subscribers] and of broadcasts held back by `min_subscribers`
+    joined: HashMap<u64, std::task::Waker>,
+    /// Set by [StreamBroadcast::pump], which prevents subscribers from polling `stream`
+    pumped: bool,
+    /// Set by [BroadcastBuilder::move_to_sole_subscriber]
+    move_sole: bool,
+    /// Set by [BroadcastBuilder::prefetch], which stops pumping once `capacity` items are ahead of the fastest subscriber
+    prefetch: bool,
+    /// Set by [BroadcastBuilder::rotate_driver]
+    rotate_driver: bool,
+    driver: Driver,
+    /// Set by [StreamBroadcast::close], which stops polling `stream`
+    closed: bool,
+    /// Set when `stream` returned `None`, so it is never polled again
+    terminated: bool,
+    poison_policy: PoisonPolicy,
+    /// Every strong and weak broadcast by id
+    subscribers: HashMap<u64, Subscriber>,
+    lag_policy: LagPolicy,
+    start: StartPos,
+    /// Items older than the duration are treated as evicted
+    ttl: Option<Duration>,
+    /// Time when each buffered item was cached, from oldest to newest. Only maintained if `ttl` is set.
+    cached_at: VecDeque<Instant>,
+    /// If set, the cache grows as needed and evicts the oldest items to stay within the maximal weight
+    weigher: Option<Weigher<T::Item>>,
+    /// Total weight of the buffered items. Only maintained if `weigher` is set.
+    weight: usize,
+    /// Grow the cache instead of evicting items
+    retain_all: bool,
+    delivery: Delivery,
+    /// Position of the next item, which no subscriber received yet. Only maintained for [Delivery::Distribute].
+    claimed: u64,
+    /// Receiver of each buffered item, from oldest to newest. Only maintained for [Delivery::RoundRobin].
+    assigned: VecDeque<Option<u64>>,
+    /// Unpaused [StreamBroadcast]s in the order they are assigned items. Only maintained for [Delivery::RoundRobin].
+    rotation: VecDeque<u64>,
+    /// Number of items lost by the underlying stream, which are not recorded as gap in the cache yet
+    gaps: Option<Arc<AtomicU64>>,
+    /// Set by [StreamBroadcast::distinct], by id
+    distinct: HashMap<u64, DedupFn<T::Item>>,
+    /// Private buffers of subscribers for items evicted before they received them, by id
+    backlogs: HashMap<u64, Backlog<T::Item>>,
+    #[cfg(feature = "tokio")]
+    heartbeat: Option<heartbeat::Heartbeat<T::Item>>,
+    /// Started when the underlying stream is held back with [LagPolicy::BackpressureFor]
+    #[cfg(feature = "tokio")]
+    backpressure_deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+    /// Set by [BroadcastBuilder::spill]
+    #[cfg(feature = "spill")]
+    spill: Option<spill::Spill<T::Item>>,
+    /// Set by [BroadcastBuilder::cancellation]
+    #[cfg(feature = "tokio-util")]
+    cancellation: Option<cancel::Cancellation>,
+    /// Set by [BroadcastBuilder::record]
+    #[cfg(feature = "recorder")]
+    recorder: Option<recorder::Recorder<T::Item>>,
+    /// Priority of new subscribers, until they call [StreamBroadcast::set_priority]
+    default_priority: i32,
+    /// Number of subscribers, whose priority differs from `default_priority`
+    prioritized: usize,
+    max_lag_observed: u64,
+    total_skips: u64,
+    dropped_unseen: u64,
+}
+
+/// Subscriber, which polls `stream` if [BroadcastBuilder::rotate_driver] is set
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Driver {
+    None,
+    /// The subscriber polled `stream` last time
+    Polled(u64),
+    /// Subscriber `to` was woken to poll `stream` next time. `from` takes the role back, if `to` didn't claim it
+    /// before `from` is polled again.
+    HandedOff {
+        to: u64,
+        from: u64,
+    },
+    /// Subscriber `by` claimed the role handed off by `from`
+    Claimed {
+        by: u64,
+        from: u64,
+    },
+}
+
+impl Driver {
+    /// Whether subscriber `id` is involved in an unclaimed hand-off
+    fn is_handing_off(self, id: u64) -> bool {
+        matches!(self, Driver::HandedOff { to, from } if to == id || from == id)
+    }
+}
+
+/// Evicted items, which are kept for subscribers lagging behind the cache. Set by [BroadcastBuilder::checkpoints].
+struct Checkpoints<I> {
+    select: CheckpointFn<I>,
+    /// Position and item, from oldest to newest
+    items: VecDeque<(u64, I)>,
+    limit: usize,
+}
+
+impl<I> Checkpoints<I> {
+    /// Oldest checkpoint at or after `pos`
+    fn find(&self, pos: u64) -> Option<(u64, &I)> {
+        self.items
+            .iter()
+            .find(|(checkpoint, _)| *checkpoint >= pos)
+            .map(|(checkpoint, x)| (*checkpoint, x))
+    }
+}
+
+/// Items of a single subscriber, which were evicted from the cache before it received them
+struct Backlog<I> {
+    /// Position and item, from oldest to newest
+    items: VecDeque<(u64, I)>,
+    limit: usize,
+}
+
+struct Subscriber {
+    pos: u64,
+    /// Weak broadcasts are ignored for backpressure
+    strong: bool,
+    /// Items assigned to this subscriber, which were evicted before it received them. Only used for [Delivery::RoundRobin].
+    missed: u64,
+    /// Set by [StreamBroadcast::pause]. Paused subscribers don't poll `stream` and are ignored for backpressure.
+    paused: bool,
+    /// Subscribers with a higher priority are woken first
+    priority: i32,
+    /// Whether the subscriber didn't receive the seed set by [BroadcastBuilder::seed] yet
+    seed_pending: bool,
+}
+
+impl<T: Stream> StreamBroadcastState<T> {
+    fn subscribe(self: Pin<&mut Self>, id: u64, pos: u64, strong: bool) {
+        let this = self.project();
+        this.subscribers.insert(
+            id,
+            Subscriber {
+                pos,
+                strong,
+                missed: 0,
+                paused: false,
+                priority: *this.default_priority,
+                seed_pending: this.seed.is_some(),
+            },
+        );
+        emit(
+            this.event_senders,
+            SubscriberEvent::Joined(SubscriberId(id)),
+        );
+        this.joined.drain().for_each(|(_, waker)| waker.wake());
+    }
+
+    /// Number of strong broadcasts
+    fn subscriber_count(&self) -> usize {
+        self.subscribers.values().filter(|s| s.strong).count()
+    }
+
+    /// Whether enough subscribers joined for [BroadcastBuilder::min_subscribers] to poll `stream`.
+    /// Otherwise, `cx` is woken when the next subscriber joins.
+    fn poll_subscribers(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>, id: u64) -> bool {
+        if self.min_subscribers == 0 {
+            return true;
+        }
+        let reached = self.subscriber_count() >= self.min_subscribers;
+        let this = self.project();
+        if reached {
+            // Later unsubscribes don't stop the stream again
+            *this.min_subscribers = 0;
+        } else {
+            this.joined.insert(id, cx.waker().clone());
+        }
+        reached
+    }
+
+    fn unsubscribe(mut self: Pin<&mut Self>, id: u64) {
+        let this = self.as_mut().project();
+        if let Some(subscriber) = this.subscribers.remove(&id) {
+            *this.prioritized -= usize::from(subscriber.priority != *this.default_priority);
+            let lag = this.global_pos.saturating_sub(subscriber.pos);
+            *this.max_lag_observed = lag.max(*this.max_lag_observed);
+            emit(
+                this.event_senders,
+                SubscriberEvent::Dropped(SubscriberId(id)),
+            );
+        }
+        this.wakable.remove(&id);
+        this.rotation.retain(|k| *k != id);
+        this.backlogs.remove(&id);
+        this.distinct.remove(&id);
+        this.private.remove(&id);
+        let handed_off = this.driver.is_handing_off(id);
+        if handed_off {
+            *this.driver = Driver::None;
+        }
+        if this.lag_policy.blocks() || *this.prefetch || handed_off {
+            self.wake_all(id);
+        }
+    }
+
+    /// Wakes another waiting subscriber to poll `stream` instead of `id`, if `id` polled it last time
+    ///
+    /// `id` is woken as well, so it takes the role back, if the other subscriber doesn't poll anymore.
+    /// Returns `false` if `id` has to poll `stream` itself.
+    fn hand_off(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>, id: u64) -> bool {
+        let this = self.project();
+        match *this.driver {
+            _ if !*this.rotate_driver => return false,
+            Driver::HandedOff { to, from } if to == id => {
+                *this.driver = Driver::Claimed { by: id, from };
+                return false;
+            }
+            Driver::HandedOff { from, .. } if from == id => {
+                event!(
+                    trace,
+                    subscriber = id,
+                    "taking back the unclaimed driver role"
+                );
+                *this.driver = Driver::Polled(id);
+                return false;
+            }
+            // The new driver wakes `id` with the next item
+            Driver::Claimed { by, from } if from == id => {
+                *this.driver = Driver::Polled(by);
+                return true;
+            }
+            Driver::Polled(last) | Driver::Claimed { by: last, .. } if last == id => {}
+            _ => {
+                *this.driver = Driver::Polled(id);
+                return false;
+            }
+        }
+        let subscribers = &*this.subscribers;
+        // The next waiting id after `id` takes over, so the role rotates among all subscribers
+        let next = this
+            .wakable
+            .keys()
+            .copied()
+            .filter(|k| *k != id && subscribers.get(k).is_some_and(|s| !s.paused))
+            .min_by_key(|k| (*k < id, *k));
+        match next.and_then(|next| this.wakable.remove_entry(&next)) {
+            Some((next, waker)) => {
+                *this.driver = Driver::HandedOff { to: next, from: id };
+                waker.wake();
+                cx.waker().wake_by_ref();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn wake_all(self: Pin<&mut Self>, except: u64) {
+        let this = self.project();
+        let mut woken = 0;
+        if *this.prioritized == 0 {
+            this.wakable.drain().for_each(|(k, w)| {
+                if k != except {
+                    w.wake();
+                    woken += 1;
+                }
+            });
+        } else {
+            let subscribers = &*this.subscribers;
+            let priority = |id: &u64| subscribers.get(id).map_or(0, |s| s.priority);
+            let mut wakers = this
+                .wakable
+                .drain()
+                .filter(|(k, _)| *k != except)
+                .collect::<Vec<_>>();
+            wakers.sort_by_key(|(k, _)| std::cmp::Reverse(priority(k)));
+            woken = wakers.len();
+            wakers.into_iter().for_each(|(_, w)| w.wake());
+        }
+        if let (Some(metrics), true) = (this.metrics, woken > 0) {
+            metrics.subscribers_woken(woken);
+        }
+    }
+
+    /// Whether polling `stream` would overwrite an item, which a strong broadcast didn't receive yet
+    fn is_backpressured(&self) -> bool {
+        self.lag_policy.blocks()
+            && !self.retain_all
+            && self.slowest(true).is_some_and(|pos| {
+                self.global_pos - pos.min(self.global_pos) >= self.cache.capacity() as u64
+            })
+    }
+
+    /// Like [is_backpressured](Self::is_backpressured), but starts the deadline of [LagPolicy::BackpressureFor]
+    /// and wakes `cx` when it elapses
+    fn poll_backpressure(self: Pin<&mut Self>, _cx: &mut std::task::Context<'_>) -> bool {
+        let backpressured = self.is_backpressured();
+        #[cfg(feature = "tokio")]
+        if let LagPolicy::BackpressureFor(duration) = self.lag_policy {
+            let deadline = self.project().backpressure_deadline;
+            if !backpressured {
+                *deadline = None;
+                return false;
+            }
+            let sleep = deadline.get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+            return std::future::Future::poll(sleep.as_mut(), _cx).is_pending();
+        }
+        backpressured
+    }
+
+    /// Position of the next item, which the slowest subscriber is going to read
+    fn slowest(&self, strong_only: bool) -> Option<u64> {
+        let mut subscribers = self
+            .subscribers
+            .values()
+            .filter(|s| (s.strong && !s.paused) || !strong_only);
+        match self.delivery {
+            Delivery::Broadcast | Delivery::RoundRobin => subscribers.map(|s| s.pos).min(),
+            Delivery::Distribute => subscribers.next().map(|_| self.claimed),
+        }
+    }
+
+    /// Whether the cache holds `capacity` items, which the fastest subscriber didn't read yet
+    fn is_prefetched(&self) -> bool {
+        let fastest = self
+            .subscribers
+            .values()
+            .filter(|s| s.strong && !s.paused)
+            .map(|s| s.pos)
+            .max()
+            .unwrap_or(self.global_pos - self.len as u64);
+        self.global_pos.saturating_sub(fastest) >= self.cache.capacity() as u64
+    }
+
+    /// Position of the next item for subscriber `id` at `pos`, depending on the [Delivery]
+    ///
+    /// Also returns the number of items for this subscriber, which were evicted before reaching the position.
+    /// Items evicted at or after the position are counted when reading it.
+    fn read_pos(&self, id: u64, pos: u64) -> (u64, u64) {
+        match self.delivery {
+            Delivery::Broadcast => (pos, 0),
+            Delivery::Distribute => (self.claimed, 0),
+            Delivery::RoundRobin => {
+                let oldest = self.oldest_available();
+                let request_pos = (pos.max(oldest)..self.global_pos)
+                    .find(|p| self.assigned[(p - oldest) as usize] == Some(id))
+                    .unwrap_or(self.global_pos);
+                let missed = self.subscribers.get(&id).map_or(0, |s| s.missed);
+                (request_pos, missed)
+            }
+        }
+    }
+
+    /// Adds a [StreamBroadcast] to the subscribers, which are assigned items for [Delivery::RoundRobin]
+    ///
+    /// Weak broadcasts and cursors don't join, so their items aren't lost to the others.
+    fn join_rotation(self: Pin<&mut Self>, id: u64) {
+        if self.delivery == Delivery::RoundRobin {
+            self.project().rotation.push_back(id);
+        }
+    }
+
+    /// Subscriber, which receives the item at `global_pos` for [Delivery::RoundRobin]
+    fn next_assignee(self: Pin<&mut Self>) -> Option<u64> {
+        let rotation = self.project().rotation;
+        let id = rotation.pop_front()?;
+        rotation.push_back(id);
+        Some(id)
+    }
+
+    /// Position at which new subscribers start reading
+    fn start_pos(&self) -> u64 {
+        match self.start {
+            StartPos::Latest => self.global_pos,
+            StartPos::Oldest => self.oldest_available(),
+        }
+    }
+
+    /// Position of the oldest item which is still buffered
+    fn oldest_available(&self) -> u64 {
+        self.global_pos - self.len as u64
+    }
+
+    /// Evicts items, which are older than `ttl`
+    fn expire(mut self: Pin<&mut Self>) {
+        let Some(ttl) = self.ttl else {
+            return;
+        };
+        let now = Instant::now();
+        while let Some(cached_at) = self.cached_at.front() {
+            if now.duration_since(*cached_at) < ttl {
+                break;
+            }
+            self.as_mut().discard_oldest();
+        }
+    }
+
+    /// Removes the oldest buffered item from the cache and passes it to [BroadcastBuilder::on_evict]
+    fn discard_oldest(mut self: Pin<&mut Self>) {
+        let item = self.as_mut().evict_oldest();
+        self.recycle(item);
+    }
+
+    fn recycle(self: Pin<&mut Self>, item: Option<Slot<T::Item>>) {
+        if let (Some(on_evict), Some(item)) =
+            (self.project().on_evict, item.and_then(Slot::into_inner))
+        {
+            on_evict(item);
+        }
+    }
+
+    /// Removes the oldest buffered item from the cache
+    fn evict_oldest(self: Pin<&mut Self>) -> Option<Slot<T::Item>> {
+        let this = self.project();
+        if *this.len == 0 {
+            return None;
+        }
+        let oldest = *this.global_pos - *this.len as u64;
+        let item = this.cache.evict(oldest);
+        *this.len -= 1;
+        if this.ttl.is_some() {
+            this.cached_at.pop_front();
+        }
+        if let (Some(weigher), Some(item)) = (this.weigher, &item) {
+            *this.weight -= (weigher.weigh)(item);
+        }
+        if let Some(Some(assignee)) = this.assigned.pop_front() {
+            if let Some(subscriber) = this.subscribers.get_mut(&assignee) {
+                if subscriber.pos <= oldest {
+                    subscriber.missed += 1;
+                }
+            }
+        }
+        item
+    }
+
+    /// Drops the buffered items and stops polling `stream`, so all subscribers terminate immediately
+    fn terminate(mut self: Pin<&mut Self>) {
+        while self.len > 0 {
+            self.as_mut().discard_oldest();
+        }
+        *self.as_mut().project().terminated = true;
+        self.wake_all(u64::MAX);
+    }
+
+    /// Replaces the underlying stream, keeping the buffered items and the positions of all subscribers
+    fn swap_source(mut self: Pin<&mut Self>, stream: T) {
+        event!(debug, "swapping underlying stream");
+        self.as_mut().project().stream.set(stream);
+        // The waker of the subscriber, which polled the previous stream, is never woken
+        self.wake_all(u64::MAX);
+    }
+
+    /// Whether no more items will be added to the cache
+    fn is_closed(&self) -> bool {
+        self.closed || self.terminated
+    }
+
+    /// Position of the first buffered item at or after `request_pos`, skipping the gaps of lost items
+    fn next_filled(&self, request_pos: u64) -> Option<u64> {
+        (request_pos.max(self.oldest_available())..self.global_pos)
+            .find(|pos| self.cache.get(*pos).is_some())
+    }
+
+    /// Bounds of the number of items subscriber `id` at `pos` receives before reaching `end`
+    ///
+    /// Only the buffered items are guaranteed to be received, unless they expire or are received by others.
+    fn size_hint(&self, id: u64, pos: u64, end: Option<u64>) -> (usize, Option<usize>) {
+        let (request_pos, _) = self.read_pos(id, pos);
+        let start = request_pos.max(self.oldest_available());
+        let buffered = (start..end.map_or(self.global_pos, |end| end.min(self.global_pos)))
+            .filter(|pos| self.cache.get(*pos).is_some())
+            .count();
+        let lower = if self.delivery == Delivery::Broadcast && self.ttl.is_none() {
+            buffered
+        } else {
+            0
+        };
+        let upstream = if self.is_closed() {
+            Some(0)
+        } else {
+            self.stream.size_hint().1
+        };
+        let upper = upstream.and_then(|upstream| upstream.checked_add(buffered));
+        let upper = match end {
+            Some(end) => {
+                let remaining = usize::try_from(end.saturating_sub(start)).unwrap_or(usize::MAX);
+                Some(upper.map_or(remaining, |upper| upper.min(remaining)))
+            }
+            None => upper,
+        };
+        (lower, upper)
+    }
+
+    /// Whether the next item for `request_pos` would be at or after `end`
+    /// Stops polling `stream`, see [StreamBroadcast::close]
+    fn close(mut self: Pin<&mut Self>, id: u64) {
+        *self.as_mut().project().closed = true;
+        event!(debug, "broadcast closed");
+        self.wake_all(id);
+    }
+
+    fn is_beyond(&self, request_pos: u64, end: Option<u64>) -> bool {
+        end.is_some_and(|end| request_pos >= end || self.oldest_available() >= end)
+    }
+
+    /// Records that subscriber `id` moved to `pos`, skipping `skipped` items on the way
+    fn advance(mut self: Pin<&mut Self>, id: u64, pos: u64, skipped: u64) {
+        let this = self.as_mut().project();
+        // The lag is largest right before the subscriber advances, so it's not tracked on every push
+        let previous = match this.delivery {
+            Delivery::Distribute => Some(*this.claimed),
+            _ => this.subscribers.get(&id).map(|s| s.pos),
+        };
+        if let Some(previous) = previous {
+            let lag = this.global_pos.saturating_sub(previous);
+            *this.max_lag_observed = lag.max(*this.max_lag_observed);
+        }
+        if let Some(subscriber) = this.subscribers.get_mut(&id) {
+            subscriber.pos = pos;
+            subscriber.missed = 0;
+        }
+        if *this.delivery == Delivery::Distribute {
+            *this.claimed = pos.max(*this.claimed);
+        }
+        *this.total_skips += skipped;
+        if skipped > 0 {
+            event!(debug, subscriber = id, skipped, "subscriber lagged behind");
+            if let Some(metrics) = this.metrics {
+                metrics.subscriber_skipped(SubscriberId(id), skipped);
+            }
+            if let Some(on_lag) = this.on_lag {
+                on_lag(SubscriberId(id), skipped);
+            }
+            let id = SubscriberId(id);
+            emit(this.event_senders, SubscriberEvent::Lagged { id, skipped });
+        }
+        if this.lag_policy.blocks() || *this.prefetch {
+            // The producer might wait for this subscriber
+            self.wake_all(id);
+        }
+    }
+
+    /// Moves `pos` right before the newest buffered item, but not beyond `end`. Returns the number of skipped items.
+    fn fast_forward(self: Pin<&mut Self>, id: u64, pos: &mut u64, end: Option<u64>) -> u64 {
+        let target = self.conflated_pos(*pos, end);
+        if target <= *pos {
+            return 0;
+        }
+        let skipped = target - *pos;
+        *pos = target;
+        self.advance(id, target, skipped);
+        skipped
+    }
+
+    /// Position of the newest buffered item before `end`, or `pos` if there is none after it
+    fn conflated_pos(&self, pos: u64, end: Option<u64>) -> u64 {
+        if self.len == 0 {
+            return pos;
+        }
+        let target = (self.global_pos - 1).min(end.map_or(u64::MAX, |end| end.saturating_sub(1)));
+        target.max(pos)
+    }
+
+    fn stats(&self) -> BroadcastStats {
+        BroadcastStats {
+            capacity: self.cache.capacity(),
+            current_len: self.len,
+            max_lag_observed: self.slowest(false).map_or(self.max_lag_observed, |pos| {
+                self.global_pos
+                    .saturating_sub(pos)
+                    .max(self.max_lag_observed)
+            }),
+            total_skips: self.total_skips,
+            items_produced: self.global_pos,
+            items_dropped_unseen: self.dropped_unseen,
+            strong_count: self.subscriber_count(),
+            weak_count: self.subscribers.len() - self.subscriber_count(),
+            min_subscriber_pos: self.subscribers.values().map(|s| s.pos).min(),
+            max_subscriber_pos: self.subscribers.values().map(|s| s.pos).max(),
+        }
+    }
 }
 
-impl<T: FusedStream> StreamBroadcastState<T>
+impl<T: Stream> StreamBroadcastState<T>
 where
     T::Item: Clone,
 {
     fn new(outer: T, size: usize) -> Self {
         Self {
             stream: outer,
-            cache: Vec::with_capacity(size), // Could be improved with  Box<[MaybeUninit<T::Item>]>
+            cache: Storage::Ring(RingStorage::new(size)),
+            len: 0,
             global_pos: Default::default(),
             wakable: Default::default(),
+            checkpoints: None,
+            seed: None,
+            dedup: None,
+            is_private: None,
+            private: Default::default(),
+            taps: Vec::new(),
+            overflow: None,
+            on_evict: None,
+            metrics: None,
+            on_lag: None,
+            event_senders: Vec::new(),
+            min_subscribers: 0,
+            joined: Default::default(),
+            pumped: false,
+            move_sole: false,
+            prefetch: false,
+            rotate_driver: false,
+            driver: Driver::None,
+            closed: false,
+            terminated: false,
+            poison_policy: PoisonPolicy::Propagate,
+            subscribers: Default::default(),
+            lag_policy: LagPolicy::DropOldest,
+            start: StartPos::Latest,
+            ttl: None,
+            cached_at: Default::default(),
+            weigher: None,
+            weight: 0,
+            retain_all: false,
+            delivery: Delivery::Broadcast,
+            claimed: 0,
+            assigned: Default::default(),
+            rotation: Default::default(),
+            gaps: None,
+            distinct: Default::default(),
+            backlogs: Default::default(),
+            #[cfg(feature = "tokio")]
+            heartbeat: None,
+            #[cfg(feature = "tokio")]
+            backpressure_deadline: None,
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "tokio-util")]
+            cancellation: None,
+            #[cfg(feature = "recorder")]
+            recorder: None,
+            default_priority: 0,
+            prioritized: 0,
+            max_lag_observed: 0,
+            total_skips: 0,
+            dropped_unseen: 0,
+        }
+    }
+
+    /// Iterates over all buffered items with their position, from oldest to newest
+    fn cached(&self) -> impl Iterator<Item = (u64, &T::Item)> {
+        (self.oldest_available()..self.global_pos).filter_map(|pos| {
+            let item = self.cache.get(pos)?;
+            Some((pos, item))
+        })
+    }
+
+    fn latest(&self) -> Option<&T::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let pos = self.global_pos - 1;
+        self.cache.get(pos)
+    }
+
+    fn poll_cached(&self, request_pos: u64) -> Option<(u64, Slot<T::Item>)> {
+        self.read_cached(request_pos, Share)
+    }
+
+    /// Passes the next buffered item for `request_pos` to `read` and returns the position after it
+    fn read_cached<R>(
+        &self,
+        request_pos: u64,
+        read: impl ReadItem<T::Item, Output = R>,
+    ) -> Option<(u64, R)> {
+        let return_pos = self.next_filled(request_pos)?;
+        let item = self
+            .cache
+            .read(return_pos, read)
+            .expect("Position is filled");
+        Some((return_pos + 1, item))
+    }
+
+    #[cfg_attr(not(feature = "tokio"), allow(dead_code))]
+    fn remove_waker(self: Pin<&mut Self>, id: u64) {
+        self.project().wakable.remove(&id);
+    }
+
+    /// Registers the waker of subscriber `id`, replacing the one of its previous poll
+    fn register_waker(self: Pin<&mut Self>, id: u64, waker: &std::task::Waker) {
+        match self.project().wakable.entry(id) {
+            Entry::Occupied(mut e) => {
+                if !e.get().will_wake(waker) {
+                    e.insert(waker.clone());
+                }
+            }
+            Entry::Vacant(e) => {
+                e.insert(waker.clone());
+            }
         }
     }
+
+    /// Polls the next item for subscriber `id` at `pos`
+    ///
+    /// Returns the number of items skipped by the subscriber, the position after the item and the item itself.
     fn poll(
         self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
-        request_pos: u64,
+        pos: u64,
         id: u64,
-    ) -> Poll<Option<(u64, T::Item)>> {
-        let this = self.project();
-        if *this.global_pos > request_pos {
-            let cap = this.cache.capacity();
-            let return_pos = if *this.global_pos - request_pos > cap as u64 {
-                *this.global_pos - cap as u64
+    ) -> Polled<Slot<T::Item>> {
+        self.poll_with(cx, pos, id, Share)
+    }
+
+    /// Like [poll](Self::poll), but passes the item to `read` by reference instead of cloning it
+    fn poll_with<R>(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        pos: u64,
+        id: u64,
+        read: impl ReadItem<T::Item, Output = R>,
+    ) -> Poll<Option<(u64, u64, R)>> {
+        self.poll_item(cx, pos, id, read, true)
+    }
+
+    /// Like [poll_with](Self::poll_with), but the item is still received by the next poll
+    ///
+    /// Repeats, which [StreamBroadcast::distinct] would skip, are peeked as well.
+    fn peek<R>(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        pos: u64,
+        id: u64,
+        read: impl ReadItem<T::Item, Output = R>,
+    ) -> Poll<Option<(u64, u64, R)>> {
+        self.poll_item(cx, pos, id, read, false)
+    }
+
+    /// Implements [poll_with](Self::poll_with) and, if `consume` isn't set, [peek](Self::peek)
+    fn poll_item<R>(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        mut pos: u64,
+        id: u64,
+        read: impl ReadItem<T::Item, Output = R>,
+        consume: bool,
+    ) -> Poll<Option<(u64, u64, R)>> {
+        self.as_mut().expire();
+        if self.subscribers.get(&id).is_some_and(|s| s.paused) {
+            if self.driver.is_handing_off(id) {
+                *self.as_mut().project().driver = Driver::None;
+                self.as_mut().wake_all(id);
+            }
+            self.register_waker(id, cx.waker());
+            return Poll::Pending;
+        }
+        let this = self.as_mut().project();
+        if let (Some(seed), Some(subscriber)) = (this.seed.as_ref(), this.subscribers.get_mut(&id))
+        {
+            if subscriber.seed_pending {
+                subscriber.seed_pending = !consume;
+                return Poll::Ready(Some((0, pos, read.read(seed))));
+            }
+        }
+        #[cfg(feature = "spill")]
+        if let Some((item_pos, x)) = self.as_mut().read_spill(pos) {
+            #[cfg(feature = "recorder")]
+            if consume {
+                self.record_consumed(id, item_pos);
+            }
+            return Poll::Ready(Some((item_pos - pos, item_pos + 1, read.read_owned(x))));
+        }
+        if let Some((item_pos, x)) = self.as_mut().pop_backlog(id, pos, consume) {
+            #[cfg(feature = "recorder")]
+            if consume {
+                self.record_consumed(id, item_pos);
+            }
+            return Poll::Ready(Some((item_pos - pos, item_pos + 1, read.read_owned(x))));
+        }
+        if pos < self.oldest_available() && self.delivery == Delivery::Broadcast {
+            let checkpoint = self.checkpoints.as_ref().and_then(|c| c.find(pos));
+            if let Some((checkpoint, x)) = checkpoint {
+                #[cfg(feature = "recorder")]
+                if consume {
+                    self.record_consumed(id, checkpoint);
+                }
+                return Poll::Ready(Some((checkpoint - pos, checkpoint + 1, read.read(x))));
+            }
+        }
+        loop {
+            let (request_pos, skipped) = self.read_pos(id, pos);
+            // Produced by the last poll of the underlying stream, so it precedes the buffered items
+            if self.private.contains_key(&id) {
+                let x = if consume {
+                    let x = self.as_mut().project().private.remove(&id);
+                    read.read_owned(x.expect("Checked before"))
+                } else {
+                    read.read(&self.private[&id])
+                };
+                return Poll::Ready(Some((skipped, request_pos, x)));
+            }
+            if let Some(next) = self.next_filled(request_pos) {
+                if consume
+                    && self.delivery == Delivery::Broadcast
+                    && self.as_mut().is_repeat(id, next)
+                {
+                    pos = next + 1;
+                    // Repeats must not hold back the underlying stream with backpressure
+                    if let Some(subscriber) = self.as_mut().project().subscribers.get_mut(&id) {
+                        subscriber.pos = subscriber.pos.max(pos);
+                    }
+                    continue;
+                }
+                // Nobody else can receive the item, so it's moved instead of cloned
+                if consume && self.move_sole && self.subscribers.len() == 1 {
+                    // The older items were already received or lost
+                    while self.oldest_available() < next {
+                        self.as_mut().discard_oldest();
+                    }
+                    #[cfg(feature = "recorder")]
+                    self.record_consumed(id, next);
+                    let x = self.as_mut().evict_oldest().expect("Position is buffered");
+                    let x = read.read_owned(x.into_owned());
+                    return Poll::Ready(Some((skipped + next - request_pos, next + 1, x)));
+                }
+                let (new_pos, x) = self
+                    .read_cached(request_pos, read)
+                    .expect("Position is buffered");
+                #[cfg(feature = "recorder")]
+                if consume {
+                    self.record_consumed(id, new_pos - 1);
+                }
+                return Poll::Ready(Some((skipped + new_pos - request_pos - 1, new_pos, x)));
+            }
+            let polled = if self.closed {
+                Poll::Ready(None)
+            } else if self.pumped {
+                if self.terminated {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Pending
+                }
+            } else if !self.as_mut().poll_subscribers(cx, id)
+                || self.as_mut().poll_backpressure(cx)
+                || self.as_mut().hand_off(cx, id)
+            {
+                Poll::Pending
             } else {
-                request_pos
+                self.as_mut().poll_source(cx, id)
             };
+            match polled {
+                Poll::Ready(Some(_)) => {}
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => {
+                    self.register_waker(id, cx.waker());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
 
-            let result = this.cache[(return_pos % cap as u64) as usize].clone();
-            return Poll::Ready(Some((return_pos + 1, result)));
+    /// Adds `x` to the cache and returns the new `global_pos`
+    fn push(mut self: Pin<&mut Self>, x: T::Item) -> u64 {
+        let this = self.as_mut().project();
+        this.taps.iter_mut().for_each(|tap| tap(&x));
+        if let Some(metrics) = this.metrics {
+            metrics.item_produced();
         }
+        self.push_slot(Some(x))
+    }
 
-        match this.stream.poll_next(cx) {
-            Poll::Ready(Some(x)) => {
-                this.wakable.drain(..).for_each(|(k, w)| {
-                    if k != id {
-                        w.wake();
+    /// Like [evict_oldest](Self::evict_oldest), but passes the item to the overflow sink, if a subscriber didn't receive it yet
+    fn overflow_oldest(mut self: Pin<&mut Self>) {
+        let oldest = self.oldest_available();
+        let unread = self.slowest(false).is_some_and(|pos| pos <= oldest);
+        if let Some(item) = self.as_mut().evict_oldest() {
+            if unread {
+                event!(
+                    debug,
+                    pos = oldest,
+                    "item evicted before all subscribers read it"
+                );
+                *self.as_mut().project().dropped_unseen += 1;
+                if let Some(metrics) = self.as_mut().project().metrics {
+                    metrics.item_dropped_unseen();
+                }
+            }
+            match (self.as_mut().project().overflow, unread) {
+                (Some(overflow), true) => overflow(item.into_owned()),
+                _ => self.recycle(Some(item)),
+            }
+        }
+    }
+
+    /// Re-indexes the buffered items into a ring with `capacity` slots, evicting the oldest items which don't fit
+    fn resize(mut self: Pin<&mut Self>, capacity: usize) {
+        assert!(capacity > 0, "capacity must be greater than 0");
+        while self.len > capacity {
+            self.as_mut().overflow_oldest();
+        }
+        let this = self.project();
+        let stored = *this.global_pos - *this.len as u64..*this.global_pos;
+        this.cache.resize(capacity, stored);
+    }
+
+    /// Evicts the oldest item to make room for a new one, after saving it in the backlogs of the subscribers,
+    /// which didn't receive it yet
+    fn wrap_oldest(mut self: Pin<&mut Self>) {
+        let oldest = self.oldest_available();
+        let this = self.as_mut().project();
+        if let Some(item) = this.cache.get(oldest) {
+            for (id, backlog) in this.backlogs.iter_mut() {
+                if this.subscribers.get(id).is_some_and(|s| s.pos <= oldest) {
+                    if backlog.items.len() == backlog.limit {
+                        backlog.items.pop_front();
+                    }
+                    backlog.items.push_back((oldest, item.clone()));
+                }
+            }
+            if let Some(checkpoints) = this.checkpoints {
+                if (checkpoints.select)(oldest, item) {
+                    checkpoints.items.push_back((oldest, item.clone()));
+                    if checkpoints.items.len() > checkpoints.limit {
+                        checkpoints.items.pop_front();
                     }
-                });
+                }
+            }
+        }
+        #[cfg(feature = "spill")]
+        if let (Some(spill), Delivery::Broadcast) = (this.spill, this.delivery) {
+            let slot = this.cache.get(oldest);
+            let result = if this.subscribers.values().any(|s| s.pos <= oldest) {
+                spill.append(oldest, slot)
+            } else {
+                spill.clear()
+            };
+            if let Err(_e) = result {
+                event!(warn, error = %_e, pos = oldest, "Failed to spill item");
+            }
+        }
+        self.overflow_oldest();
+    }
 
-                let cap = this.cache.capacity();
-                if this.cache.len() < cap {
-                    this.cache.push(x.clone());
-                } else {
-                    this.cache[(*this.global_pos % cap as u64) as usize] = x.clone();
+    /// Records that subscriber `id` received the item at `pos`
+    #[cfg(feature = "recorder")]
+    fn record_consumed(&self, id: u64, pos: u64) {
+        if let Some(recorder) = &self.recorder {
+            recorder.consumed(pos, SubscriberId(id));
+        }
+    }
+
+    /// Reads the item at `pos` or the next one from the spill file, if `pos` was evicted from the cache
+    #[cfg(feature = "spill")]
+    fn read_spill(self: Pin<&mut Self>, mut pos: u64) -> Option<(u64, T::Item)> {
+        let oldest = self.oldest_available();
+        let spill = self.project().spill.as_mut()?;
+        while pos < oldest {
+            match spill.read(pos)? {
+                (item_pos, Ok(x)) => return Some((item_pos, x)),
+                (_item_pos, Err(_e)) => {
+                    event!(warn, error = %_e, pos = _item_pos, "Failed to read spilled item");
+                    pos = _item_pos + 1;
                 }
-                *this.global_pos += 1;
-                let result = (*this.global_pos, x);
-                Poll::Ready(Some(result))
             }
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Pending => {
-                this.wakable.push((id, cx.waker().clone()));
-                Poll::Pending
+        }
+        None
+    }
+
+    /// Whether the item at `pos` equals the last item received by subscriber `id`, if it called [StreamBroadcast::distinct]
+    fn is_repeat(self: Pin<&mut Self>, id: u64, pos: u64) -> bool {
+        let this = self.project();
+        match (this.distinct.get_mut(&id), this.cache.get(pos)) {
+            (Some(is_repeat), Some(x)) => is_repeat(x),
+            _ => false,
+        }
+    }
+
+    /// Removes the oldest item of the backlog of subscriber `id` at `pos`, or only clones it if `consume` isn't set
+    fn pop_backlog(
+        self: Pin<&mut Self>,
+        id: u64,
+        pos: u64,
+        consume: bool,
+    ) -> Option<(u64, T::Item)> {
+        let backlog = self.project().backlogs.get_mut(&id)?;
+        while backlog
+            .items
+            .front()
+            .is_some_and(|(item_pos, _)| *item_pos < pos)
+        {
+            backlog.items.pop_front();
+        }
+        if consume {
+            backlog.items.pop_front()
+        } else {
+            backlog.items.front().cloned()
+        }
+    }
+
+    /// Records `n` items lost by the underlying stream, which are reported as skipped to all subscribers
+    fn push_gap(mut self: Pin<&mut Self>, n: u64) {
+        let grows = self.weigher.is_some() || self.retain_all;
+        if !grows && n >= self.cache.capacity() as u64 {
+            while self.len > 0 {
+                self.as_mut().wrap_oldest();
+            }
+            *self.project().global_pos += n;
+        } else {
+            for _ in 0..n {
+                self.as_mut().push_slot(None);
+            }
+        }
+    }
+
+    /// Adds an item or a gap to the cache and returns the new `global_pos`
+    fn push_slot(mut self: Pin<&mut Self>, slot: Option<T::Item>) -> u64 {
+        let weight = self
+            .weigher
+            .as_ref()
+            .map(|w| (w.max, slot.as_ref().map_or(0, |x| (w.weigh)(x))));
+        if let Some((max, weight)) = weight {
+            while self.len > 0 && self.weight + weight > max {
+                self.as_mut().wrap_oldest();
             }
         }
+        if self.len == self.cache.capacity() {
+            if self.weigher.is_some() || self.retain_all {
+                let capacity = (self.cache.capacity() * 2).max(1);
+                self.as_mut().resize(capacity);
+            } else {
+                self.as_mut().wrap_oldest();
+            }
+        }
+
+        let assignee = self.as_mut().next_assignee();
+        let this = self.project();
+        #[cfg(feature = "recorder")]
+        if let Some(recorder) = this.recorder {
+            recorder.produced(*this.global_pos, slot.as_ref());
+        }
+        this.cache.push(*this.global_pos, slot);
+        if this.ttl.is_some() {
+            this.cached_at.push_back(Instant::now());
+        }
+        if let Some((_, weight)) = weight {
+            *this.weight += weight;
+        }
+        if *this.delivery == Delivery::RoundRobin {
+            this.assigned.push_back(assignee);
+        }
+        *this.len += 1;
+        *this.global_pos += 1;
+        event!(trace, pos = *this.global_pos - 1, "item cached");
+        *this.global_pos
+    }
+
+    /// Polls the underlying stream for [StreamBroadcast::pump] until it terminates
+    fn poll_pump(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>, id: u64) -> Poll<()> {
+        for _ in 0..self.cache.capacity() {
+            if !self.as_mut().poll_subscribers(cx, id) {
+                return Poll::Pending;
+            }
+            if self.as_mut().poll_backpressure(cx) || (self.prefetch && self.is_prefetched()) {
+                self.register_waker(id, cx.waker());
+                return Poll::Pending;
+            }
+            match self.as_mut().poll_source(cx, id) {
+                Poll::Ready(Some(_)) => {}
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+
+    /// Polls the underlying stream and caches the received item. Wakes all others on completion
+    fn poll_source(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        id: u64,
+    ) -> Poll<Option<u64>> {
+        if self.is_closed() {
+            return Poll::Ready(None);
+        }
+        #[cfg(feature = "tokio-util")]
+        if let Some(cancellation) = self.as_mut().project().cancellation {
+            // Polled first, so the waiting subscriber is woken by the token as well
+            if cancellation.poll(cx).is_ready() {
+                event!(debug, "broadcast cancelled");
+                if cancellation.drain {
+                    *self.as_mut().project().closed = true;
+                } else {
+                    self.as_mut().terminate();
+                }
+                self.wake_all(id);
+                return Poll::Ready(None);
+            }
+        }
+        event!(trace, subscriber = id, "polling underlying stream");
+        let stream = self.as_mut().project().stream;
+        // A panic would poison the lock for all subscribers, so the broadcast terminates instead
+        let polled = std::panic::catch_unwind(AssertUnwindSafe(|| stream.poll_next(cx)))
+            .unwrap_or_else(|_| {
+                event!(warn, "underlying stream panicked");
+                Poll::Ready(None)
+            });
+        let result = match polled {
+            Poll::Ready(Some(x)) if self.is_private.is_some_and(|f| f(&x)) => {
+                event!(trace, "passing private item to the polling subscriber");
+                self.as_mut().project().private.insert(id, x);
+                return Poll::Ready(Some(self.global_pos));
+            }
+            Poll::Ready(Some(x))
+                if self
+                    .as_mut()
+                    .project()
+                    .dedup
+                    .as_mut()
+                    .is_some_and(|f| f(&x)) =>
+            {
+                // Duplicates don't get a position, so the offsets only count items, which were cached
+                event!(trace, "skipping duplicate item");
+                return Poll::Ready(Some(self.global_pos));
+            }
+            Poll::Ready(Some(x)) => {
+                let lost = self
+                    .gaps
+                    .as_ref()
+                    .map_or(0, |gaps| gaps.swap(0, Ordering::SeqCst));
+                self.as_mut().push_gap(lost);
+                #[cfg(feature = "tokio")]
+                if let Some(heartbeat) = self.as_mut().project().heartbeat {
+                    heartbeat.reset();
+                }
+                Poll::Ready(Some(self.as_mut().push(x)))
+            }
+            Poll::Ready(None) => {
+                event!(debug, "underlying stream terminated");
+                *self.as_mut().project().terminated = true;
+                Poll::Ready(None)
+            }
+            #[cfg(feature = "tokio")]
+            Poll::Pending if self.heartbeat.is_some() => {
+                let heartbeat = self.as_mut().project().heartbeat.as_mut();
+                match heartbeat.expect("Checked before").poll(cx) {
+                    Poll::Ready(x) => Poll::Ready(Some(self.as_mut().push(x))),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            Poll::Pending => return Poll::Pending,
+        };
+        self.wake_all(id);
+        result
     }
 }
@@ -0,0 +1,278 @@
+use futures::stream::{FusedStream, Stream};
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::Poll,
+};
+
+use super::{
+    broadast_next, create_id, into_owned, try_next_cached, BroadcastStats, StreamBroadcastState,
+};
+
+/// State shared by [LocalStreamBroadcast]s
+struct LocalState<T: Stream> {
+    state: RefCell<Pin<Box<StreamBroadcastState<T>>>>,
+    /// Subscribers dropped while the state was borrowed, e.g. by a callback
+    unsubscribed: Cell<Vec<u64>>,
+}
+
+impl<T: Stream> LocalState<T> {
+    fn new(state: StreamBroadcastState<T>) -> Self {
+        Self {
+            state: RefCell::new(Box::pin(state)),
+            unsubscribed: Default::default(),
+        }
+    }
+
+    /// Borrows the state after applying the deferred unsubscribes
+    ///
+    /// # Panics
+    /// Panics if the state is already borrowed
+    fn borrow_mut(&self) -> RefMut<'_, Pin<Box<StreamBroadcastState<T>>>> {
+        let mut state = self.state.borrow_mut();
+        for id in self.unsubscribed.take() {
+            state.as_mut().unsubscribe(id);
+        }
+        state
+    }
+
+    /// Unsubscribes `id` now or, if the state is borrowed, with the next borrow
+    fn unsubscribe(&self, id: u64) {
+        match self.state.try_borrow_mut() {
+            Ok(mut state) => state.as_mut().unsubscribe(id),
+            Err(_) => {
+                let mut unsubscribed = self.unsubscribed.take();
+                unsubscribed.push(id);
+                self.unsubscribed.set(unsubscribed);
+            }
+        }
+    }
+}
+
+/// Created by [broadcast_local](crate::StreamBroadcastExt::broadcast_local)
+///
+/// Like [StreamBroadcast](crate::StreamBroadcast), but shares its state with [Rc] and [RefCell] instead of
+/// [Arc](std::sync::Arc) and [Mutex](std::sync::Mutex). It can't be sent to other threads, which avoids the
+/// synchronization costs on single threaded executors.
+///
+/// Only the core of the API is supported: [close_after](Self::close_after), [close](Self::close),
+/// [try_next_now](Self::try_next_now), [downgrade](Self::downgrade), [stats](Self::stats), [lag](Self::lag)
+/// and [position](Self::position). All other methods of [StreamBroadcast](crate::StreamBroadcast) are not
+/// available, including the configuration with a [BroadcastBuilder](crate::BroadcastBuilder) and all adapters.
+pub struct LocalStreamBroadcast<T: Stream> {
+    pos: u64,
+    id: u64,
+    close_after: Option<u64>,
+    state: Rc<LocalState<T>>,
+}
+
+impl<T: Stream> LocalStreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    pub fn new(stream: T, size: usize) -> Self {
+        let state = StreamBroadcastState::new(stream, size);
+        Self::with_pos(Rc::new(LocalState::new(state)), 0)
+    }
+
+    /// Returns the next item if it is already buffered, see
+    /// [StreamBroadcast::try_next_now](crate::StreamBroadcast::try_next_now)
+    pub fn try_next_now(&mut self) -> Option<Option<(u64, T::Item)>> {
+        let mut state = self.state.borrow_mut();
+        let read = try_next_cached(
+            state.as_mut(),
+            &mut self.pos,
+            self.id,
+            self.close_after,
+            false,
+        );
+        drop(state);
+        Some(read?.map(|(offset, x)| (offset, x.into_owned())))
+    }
+}
+
+impl<T: Stream> LocalStreamBroadcast<T> {
+    fn with_pos(state: Rc<LocalState<T>>, pos: u64) -> Self {
+        let id = create_id();
+        state.borrow_mut().as_mut().subscribe(id, pos, true);
+        Self {
+            pos,
+            id,
+            close_after: None,
+            state,
+        }
+    }
+
+    /// Terminates after the item at position `n - 1`, see
+    /// [StreamBroadcast::close_after](crate::StreamBroadcast::close_after)
+    pub fn close_after(&mut self, n: u64) {
+        self.close_after = Some(n);
+    }
+
+    /// Stops polling the underlying stream for all broadcasts, see [StreamBroadcast::close](crate::StreamBroadcast::close)
+    pub fn close(&self) {
+        self.state.borrow_mut().as_mut().close(self.id);
+    }
+
+    /// Creates a weak broadcast, see [StreamBroadcast::downgrade](crate::StreamBroadcast::downgrade)
+    ///
+    /// ```
+    /// # futures::executor::block_on(async {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut broadcast = futures::stream::iter(0..3).broadcast_local(3);
+    /// let mut weak = broadcast.downgrade();
+    /// assert_eq!(Some((0, 0)), broadcast.next().await);
+    /// assert_eq!(Some((0, 0)), weak.next().await);
+    /// drop(broadcast);
+    /// assert_eq!(None, weak.next().await);
+    /// # });
+    /// ```
+    pub fn downgrade(&self) -> WeakLocalStreamBroadcast<T> {
+        WeakLocalStreamBroadcast::new(Rc::downgrade(&self.state), self.pos)
+    }
+
+    /// Statistics of the shared cache, see [StreamBroadcast::stats](crate::StreamBroadcast::stats)
+    pub fn stats(&self) -> BroadcastStats {
+        self.state.borrow_mut().stats()
+    }
+
+    /// Number of items produced by the underlying stream, which this broadcast didn't receive yet
+    pub fn lag(&self) -> u64 {
+        self.state.borrow_mut().global_pos.saturating_sub(self.pos)
+    }
+
+    /// Position of the next item to receive, which is the sum of all offsets and items received so far
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<T: Stream> Clone for LocalStreamBroadcast<T> {
+    fn clone(&self) -> Self {
+        let pos = self.state.borrow_mut().start_pos();
+        Self::with_pos(self.state.clone(), pos)
+    }
+}
+
+impl<T: Stream> Drop for LocalStreamBroadcast<T> {
+    fn drop(&mut self) {
+        self.state.unsubscribe(self.id);
+    }
+}
+
+impl<T: Stream> Stream for LocalStreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut state = this.state.borrow_mut();
+        state.as_mut().expire();
+        if state.is_beyond(this.pos, this.close_after) {
+            return Poll::Ready(None);
+        }
+        let polled = broadast_next(state.as_mut(), cx, &mut this.pos, this.id);
+        drop(state);
+        into_owned(polled)
+    }
+}
+
+impl<T: Stream> FusedStream for LocalStreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        let state = self.state.borrow_mut();
+        state.is_closed() || state.is_beyond(self.pos, self.close_after)
+    }
+}
+
+/// Created by [downgrade](LocalStreamBroadcast::downgrade)
+///
+/// Like [WeakStreamBroadcast](crate::WeakStreamBroadcast), it terminates once all [LocalStreamBroadcast]s were
+/// dropped.
+pub struct WeakLocalStreamBroadcast<T: Stream> {
+    pos: u64,
+    id: u64,
+    state: Weak<LocalState<T>>,
+}
+
+impl<T: Stream> WeakLocalStreamBroadcast<T> {
+    fn new(state: Weak<LocalState<T>>, pos: u64) -> Self {
+        let id = create_id();
+        if let Some(state) = state.upgrade() {
+            state.borrow_mut().as_mut().subscribe(id, pos, false);
+        }
+        Self { pos, id, state }
+    }
+
+    /// Upgrades to a [LocalStreamBroadcast], whose existence keeps the stream running
+    pub fn upgrade(&self) -> Option<LocalStreamBroadcast<T>> {
+        let state = self.state.upgrade()?;
+        Some(LocalStreamBroadcast::with_pos(state, self.pos))
+    }
+
+    /// Position of the next item to receive, like [LocalStreamBroadcast::position]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+impl<T: Stream> Clone for WeakLocalStreamBroadcast<T> {
+    fn clone(&self) -> Self {
+        let pos = self
+            .state
+            .upgrade()
+            .map(|s| s.borrow_mut().start_pos())
+            .unwrap_or(self.pos);
+        Self::new(self.state.clone(), pos)
+    }
+}
+
+impl<T: Stream> Drop for WeakLocalStreamBroadcast<T> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            state.unsubscribe(self.id);
+        }
+    }
+}
+
+impl<T: Stream> Stream for WeakLocalStreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let Some(state) = this.state.upgrade() else {
+            return Poll::Ready(None);
+        };
+        let mut state = state.borrow_mut();
+        let polled = broadast_next(state.as_mut(), cx, &mut this.pos, this.id);
+        drop(state);
+        into_owned(polled)
+    }
+}
+
+impl<T: Stream> FusedStream for WeakLocalStreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.state
+            .upgrade()
+            .is_none_or(|state| state.borrow_mut().is_closed())
+    }
+}
@@ -0,0 +1,79 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{ops::DerefMut, pin::Pin, task::Poll};
+
+use super::{broadcast_next_with, lock_state, StreamBroadcast};
+
+/// Created by [subscribe_with](crate::StreamBroadcast::subscribe_with)
+///
+/// Yields the result of the projection instead of a clone of the item.
+#[pin_project]
+pub struct MappedBroadcast<T: Stream, F> {
+    inner: StreamBroadcast<T>,
+    projection: F,
+    /// Items skipped since the last yielded item
+    skipped: u64,
+}
+
+impl<T: Stream, F> MappedBroadcast<T, F> {
+    pub(crate) fn new(inner: StreamBroadcast<T>, projection: F) -> Self {
+        Self {
+            inner,
+            projection,
+            skipped: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream, F, U> Stream for MappedBroadcast<T, F>
+where
+    T::Item: Clone,
+    F: FnMut(&T::Item) -> U,
+{
+    type Item = (u64, U);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let inner = this.inner;
+        let mut lock = lock_state(&inner.state);
+        lock.as_mut().expire();
+        if lock.is_beyond(inner.pos, inner.close_after) {
+            return Poll::Ready(None);
+        }
+        let projection = this.projection;
+        let mut read = |x: &T::Item| Some(projection(x));
+        broadcast_next_with(
+            lock.deref_mut().as_mut(),
+            cx,
+            &mut inner.pos,
+            inner.id,
+            this.skipped,
+            &mut read,
+        )
+        .map(|x| {
+            x.map(|(offset, x)| {
+                (
+                    inner.offset_mode.report(&mut inner.skipped_total, offset),
+                    x,
+                )
+            })
+        })
+    }
+}
+
+impl<T: Stream, F, U> FusedStream for MappedBroadcast<T, F>
+where
+    T::Item: Clone,
+    F: FnMut(&T::Item) -> U,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
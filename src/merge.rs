@@ -0,0 +1,108 @@
+use futures::stream::{FusedStream, Stream};
+use std::{pin::Pin, task::Poll};
+
+use super::{lock_state, StreamBroadcast};
+
+/// Underlying stream of [merge](StreamBroadcast::merge), which yields the items of all sources with their index
+pub struct MergeSource<S> {
+    /// Sources in the order passed to [merge](StreamBroadcast::merge) and added by
+    /// [add_source](StreamBroadcast::add_source). Terminated ones are `None`.
+    sources: Vec<Option<Pin<Box<S>>>>,
+    /// Index of the source, which is polled first next time
+    next: usize,
+}
+
+impl<S: Stream> StreamBroadcast<MergeSource<S>>
+where
+    S::Item: Clone,
+{
+    /// Creates a single broadcast of the items of all `sources`
+    ///
+    /// Each item is yielded with the index of its source in `sources`. The sources are polled in turns, so
+    /// a busy source can't starve the others. The broadcast terminates after all sources terminated.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcast;
+    ///
+    /// let broadcast = StreamBroadcast::merge([futures::stream::iter(0..2), futures::stream::iter(5..8)], 5);
+    /// let items = broadcast.map(|(_, x)| x).collect::<Vec<_>>().await;
+    /// assert_eq!(vec![(0, 0), (1, 5), (0, 1), (1, 6), (1, 7)], items);
+    /// # }
+    /// ```
+    pub fn merge(sources: impl IntoIterator<Item = S>, size: usize) -> Self {
+        let source = MergeSource {
+            sources: sources.into_iter().map(|s| Some(Box::pin(s))).collect(),
+            next: 0,
+        };
+        Self::new(source, size)
+    }
+
+    /// Adds `source` to the live broadcast and returns the index, with which its items are yielded
+    ///
+    /// The items of all sources share a single sequence of positions. Sources added after all previous sources
+    /// terminated are never polled, because the broadcast terminated already.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcast;
+    ///
+    /// let (tx, rx) = futures::channel::mpsc::unbounded();
+    /// let mut broadcast = StreamBroadcast::merge([rx.boxed()], 5);
+    /// tx.unbounded_send(1).unwrap();
+    /// assert_eq!(Some((0, (0, 1))), broadcast.next().await);
+    /// assert_eq!(1, broadcast.add_source(futures::stream::iter([2]).boxed()));
+    /// drop(tx);
+    /// assert_eq!(vec![(0, (1, 2))], broadcast.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn add_source(&self, source: S) -> usize {
+        let mut lock = lock_state(&self.state);
+        let merge = lock.as_mut().project().stream.get_mut();
+        merge.sources.push(Some(Box::pin(source)));
+        let index = merge.sources.len() - 1;
+        // Waiting broadcasts only registered their wakers at the previous sources
+        lock.as_mut().wake_all(self.id);
+        index
+    }
+}
+
+impl<S: Stream> Stream for MergeSource<S> {
+    type Item = (usize, S::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let len = this.sources.len();
+        for i in (0..len).map(|i| (this.next + i) % len) {
+            let Some(source) = &mut this.sources[i] else {
+                continue;
+            };
+            match source.as_mut().poll_next(cx) {
+                Poll::Ready(Some(x)) => {
+                    this.next = (i + 1) % len;
+                    return Poll::Ready(Some((i, x)));
+                }
+                Poll::Ready(None) => this.sources[i] = None,
+                Poll::Pending => {}
+            }
+        }
+        if this.is_terminated() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S: Stream> FusedStream for MergeSource<S> {
+    fn is_terminated(&self) -> bool {
+        self.sources.iter().all(Option::is_none)
+    }
+}
@@ -0,0 +1,60 @@
+/// Identifies a subscriber of a broadcast, see [subscriber_id](crate::StreamBroadcast::subscriber_id)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubscriberId(pub(crate) u64);
+
+/// Receives the counters of a broadcast, installed with [metrics](crate::BroadcastBuilder::metrics)
+///
+/// The methods are called while the shared state is locked and should return quickly, e.g. by incrementing
+/// a counter of a metrics library. All of them do nothing by default.
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use futures::StreamExt;
+/// use stream_broadcast::{BroadcastMetrics, StreamBroadcastExt, SubscriberId};
+/// use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+///
+/// struct Skips(Arc<AtomicU64>);
+///
+/// impl BroadcastMetrics for Skips {
+///     fn subscriber_skipped(&mut self, _subscriber: SubscriberId, skipped: u64) {
+///         self.0.fetch_add(skipped, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let skips = Arc::new(AtomicU64::new(0));
+/// let mut broadcast = futures::stream::iter(0..5)
+///     .broadcast_builder()
+///     .capacity(2)
+///     .metrics(Skips(skips.clone()))
+///     .build();
+/// let slow = broadcast.clone();
+/// assert_eq!(5, (&mut broadcast).count().await);
+/// assert_eq!(2, slow.count().await);
+/// assert_eq!(3, skips.load(Ordering::Relaxed));
+/// # }
+/// ```
+pub trait BroadcastMetrics: Send {
+    /// An item was received from the underlying stream
+    fn item_produced(&mut self) {}
+
+    /// An item was evicted before all subscribers received it
+    fn item_dropped_unseen(&mut self) {}
+
+    /// `subscriber` skipped `skipped` items, because it fell behind
+    fn subscriber_skipped(&mut self, _subscriber: SubscriberId, _skipped: u64) {}
+
+    /// `woken` subscribers waiting for the next item were woken
+    fn subscribers_woken(&mut self, _woken: usize) {}
+}
+
+/// Change of the subscribers of a broadcast, yielded by [events](crate::StreamBroadcast::events)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriberEvent {
+    /// A clone or weak handle was created
+    Joined(SubscriberId),
+    /// A clone or weak handle was dropped
+    Dropped(SubscriberId),
+    /// The subscriber skipped `skipped` items, because it fell behind
+    Lagged { id: SubscriberId, skipped: u64 },
+}
@@ -0,0 +1,56 @@
+use std::sync::{Arc, Mutex};
+
+/// Evicted items, which are reused for new items instead of allocating them, see
+/// [recycle](crate::BroadcastBuilder::recycle)
+///
+/// The items are returned as they were evicted, so e.g. a `Vec` has to be cleared before it's reused.
+pub struct ItemPool<I> {
+    items: Arc<Mutex<Vec<I>>>,
+    limit: usize,
+}
+
+impl<I> ItemPool<I> {
+    /// Creates an empty pool, which keeps at most `limit` items and drops the others
+    pub fn new(limit: usize) -> Self {
+        Self {
+            items: Default::default(),
+            limit,
+        }
+    }
+
+    /// Takes a recycled item, if one is available
+    pub fn take(&self) -> Option<I> {
+        self.lock().pop()
+    }
+
+    /// Returns `item` to the pool, unless it is full
+    pub fn put(&self, item: I) {
+        let mut items = self.lock();
+        if items.len() < self.limit {
+            items.push(item);
+        }
+    }
+
+    /// Number of items in the pool
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<I>> {
+        // The items are valid, even if another thread panicked
+        self.items.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<I> Clone for ItemPool<I> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            limit: self.limit,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Recording the history of a broadcast for debugging, available with the `recorder` feature
+
+use std::{
+    collections::BTreeMap,
+    ops::RangeBounds,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use super::SubscriberId;
+
+/// Keeps every item of a broadcast together with the subscribers, which received it, see
+/// [record](crate::BroadcastBuilder::record)
+///
+/// The history is separate from the cache, so it isn't affected by evictions and grows without bounds until it's
+/// [cleared](Self::clear).
+///
+/// ```
+/// # #[tokio::main]
+/// # async fn main() {
+/// use futures::StreamExt;
+/// use stream_broadcast::{Recorder, StreamBroadcastExt};
+///
+/// let recorder = Recorder::new();
+/// let mut broadcast = futures::stream::iter(0..3).fuse().broadcast_builder().record(&recorder).build();
+/// let id = broadcast.subscriber_id();
+/// assert_eq!(Some((0, 0)), broadcast.next().await);
+/// let record = recorder.get(0).unwrap();
+/// assert_eq!(Some(0), record.item);
+/// assert_eq!(vec![id], record.consumed.iter().map(|c| c.subscriber).collect::<Vec<_>>());
+/// assert_eq!(vec![0], recorder.consumed_by(id).iter().map(|(pos, _)| *pos).collect::<Vec<_>>());
+/// # }
+/// ```
+pub struct Recorder<I> {
+    history: Arc<Mutex<BTreeMap<u64, RecordedItem<I>>>>,
+}
+
+/// Item at [pos](Self::pos) in the history of a [Recorder]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedItem<I> {
+    pub pos: u64,
+    /// `None` for an item lost by the underlying stream
+    pub item: Option<I>,
+    pub produced_at: SystemTime,
+    /// Subscribers, which received the item, in the order they did so
+    pub consumed: Vec<Consumption>,
+}
+
+/// Receipt of a [RecordedItem] by a subscriber
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Consumption {
+    pub subscriber: SubscriberId,
+    pub at: SystemTime,
+}
+
+impl<I> Recorder<I> {
+    pub fn new() -> Self {
+        Self {
+            history: Default::default(),
+        }
+    }
+
+    /// Number of recorded positions
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets the recorded history. Later items are still recorded.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Positions received by `subscriber` and when it received them
+    pub fn consumed_by(&self, subscriber: SubscriberId) -> Vec<(u64, SystemTime)> {
+        self.lock()
+            .values()
+            .flat_map(|record| {
+                record
+                    .consumed
+                    .iter()
+                    .filter(|c| c.subscriber == subscriber)
+                    .map(|c| (record.pos, c.at))
+            })
+            .collect()
+    }
+
+    pub(crate) fn consumed(&self, pos: u64, subscriber: SubscriberId) {
+        if let Some(record) = self.lock().get_mut(&pos) {
+            record.consumed.push(Consumption {
+                subscriber,
+                at: SystemTime::now(),
+            });
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, RecordedItem<I>>> {
+        // The history is valid, even if another thread panicked
+        self.history.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<I: Clone> Recorder<I> {
+    /// Item at `pos`, if it was recorded
+    pub fn get(&self, pos: u64) -> Option<RecordedItem<I>> {
+        self.lock().get(&pos).cloned()
+    }
+
+    /// Recorded items within `range`, ordered by position
+    pub fn range(&self, range: impl RangeBounds<u64>) -> Vec<RecordedItem<I>> {
+        self.lock()
+            .range(range)
+            .map(|(_, record)| record.clone())
+            .collect()
+    }
+
+    pub(crate) fn produced(&self, pos: u64, item: Option<&I>) {
+        let record = RecordedItem {
+            pos,
+            item: item.cloned(),
+            produced_at: SystemTime::now(),
+            consumed: Vec::new(),
+        };
+        self.lock().insert(pos, record);
+    }
+}
+
+impl<I> Default for Recorder<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> Clone for Recorder<I> {
+    fn clone(&self) -> Self {
+        Self {
+            history: self.history.clone(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use futures::stream::{FusedStream, Stream, StreamExt};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::Poll,
+};
+
+use super::{StreamBroadcast, StreamBroadcastState};
+
+/// Created by [relay](StreamBroadcast::relay)
+pub type Relay<T> = StreamBroadcast<RelaySource<T>>;
+
+/// Underlying stream of a [Relay], which is a subscriber of the root broadcast
+pub struct RelaySource<T: Stream> {
+    inner: StreamBroadcast<T>,
+    /// Items skipped by `inner`, which the relay reports as skipped
+    gaps: Arc<AtomicU64>,
+}
+
+impl<T: Stream> StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    /// Creates a broadcast with its own cache of `size` items, which receives the items of this broadcast
+    ///
+    /// Subscribers of different relays don't share a lock, which reduces contention with thousands of
+    /// subscribers. The relay subscribes to this broadcast like a clone and only polls it, if one of its own
+    /// subscribers needs the next item. Items skipped by the relay are reported as skipped to its subscribers.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let root = futures::stream::iter(0..4).fuse().broadcast(4);
+    /// let shards = [root.relay(2), root.relay(2)];
+    /// drop(root);
+    /// for shard in shards {
+    ///     let subscriber = shard.clone();
+    ///     assert_eq!(4, shard.count().await);
+    ///     assert_eq!(vec![(2, 2), (0, 3)], subscriber.collect::<Vec<_>>().await);
+    /// }
+    /// # }
+    /// ```
+    pub fn relay(&self, size: usize) -> Relay<T> {
+        let gaps = Arc::new(AtomicU64::new(0));
+        let source = RelaySource {
+            inner: self.clone().per_item(),
+            gaps: gaps.clone(),
+        };
+        let mut state = StreamBroadcastState::new(source, size);
+        state.gaps = Some(gaps);
+        StreamBroadcast::from_state(state)
+    }
+}
+
+impl<T: Stream> Stream for RelaySource<T>
+where
+    T::Item: Clone,
+{
+    type Item = T::Item;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let polled = futures::ready!(self.inner.poll_next_unpin(cx));
+        Poll::Ready(polled.map(|(offset, x)| {
+            self.gaps.fetch_add(offset, Ordering::SeqCst);
+            x
+        }))
+    }
+}
+
+impl<T: Stream> FusedStream for RelaySource<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
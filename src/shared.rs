@@ -0,0 +1,40 @@
+use futures::stream::{Map, Stream, StreamExt};
+use std::sync::Arc;
+
+use super::{StreamBroadcast, StreamBroadcastExt};
+
+/// Underlying stream of an [ArcBroadcast], which wraps each item into an [Arc]
+pub type ArcStream<T> = Map<T, fn(<T as Stream>::Item) -> Arc<<T as Stream>::Item>>;
+
+/// Created by [broadcast_arc](crate::StreamBroadcastExt::broadcast_arc) and
+/// [broadcast_shared](SharedBroadcastExt::broadcast_shared)
+pub type ArcBroadcast<T> = StreamBroadcast<ArcStream<T>>;
+
+/// Broadcasts streams, whose items don't implement [Clone]
+pub trait SharedBroadcastExt: Stream + Sized {
+    /// Like [broadcast](crate::StreamBroadcastExt::broadcast), but wraps each item in an [Arc], so the items
+    /// don't have to implement [Clone]
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::SharedBroadcastExt;
+    ///
+    /// struct NotClone(u32);
+    ///
+    /// let broadcast = futures::stream::iter([NotClone(1), NotClone(2)]).fuse().broadcast_shared(3);
+    /// let broadcast2 = broadcast.clone();
+    /// assert_eq!(vec![1, 2], broadcast.map(|(_, x)| x.0).collect::<Vec<_>>().await);
+    /// assert_eq!(2, broadcast2.count().await);
+    /// # }
+    /// ```
+    fn broadcast_shared(self, size: usize) -> ArcBroadcast<Self>;
+}
+
+impl<T: Stream> SharedBroadcastExt for T {
+    fn broadcast_shared(self, size: usize) -> ArcBroadcast<Self> {
+        let wrap: fn(T::Item) -> Arc<T::Item> = Arc::new;
+        self.map(wrap).broadcast(size)
+    }
+}
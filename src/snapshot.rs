@@ -0,0 +1,71 @@
+//! Persisting the buffered items across restarts, available with the `serde` feature
+
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use super::{lock_state, StreamBroadcast, StreamBroadcastState};
+
+/// Buffered items of a broadcast, created by [snapshot](StreamBroadcast::snapshot)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastSnapshot<I> {
+    /// Position of the next item produced by the underlying stream
+    pub global_pos: u64,
+    /// Buffered items up to `global_pos`, where `None` is an item lost by the underlying stream
+    pub items: Vec<Option<I>>,
+}
+
+impl<T: Stream> StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    /// Copies the buffered items and the current position of the underlying stream
+    ///
+    /// Together with [restore](Self::restore) and [subscribe_at](Self::subscribe_at), subscribers of a restarted
+    /// process resume where they stopped, as long as their position was still buffered.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::{BroadcastSnapshot, StreamBroadcast, StreamBroadcastExt};
+    ///
+    /// let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(3);
+    /// assert_eq!(5, (&mut broadcast).count().await);
+    /// let json = serde_json::to_string(&broadcast.snapshot()).unwrap();
+    ///
+    /// let snapshot: BroadcastSnapshot<i32> = serde_json::from_str(&json).unwrap();
+    /// let restored = StreamBroadcast::restore(futures::stream::iter(5..7), 3, snapshot);
+    /// let resumed = restored.subscribe_at(3).ok().unwrap();
+    /// assert_eq!(vec![(0, 3), (0, 4), (0, 5), (0, 6)], resumed.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn snapshot(&self) -> BroadcastSnapshot<T::Item> {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        let items = (lock.oldest_available()..lock.global_pos)
+            .map(|pos| lock.cache.get(pos).cloned())
+            .collect();
+        BroadcastSnapshot {
+            global_pos: lock.global_pos,
+            items,
+        }
+    }
+
+    /// Creates a broadcast of `stream`, which continues after the items of `snapshot`
+    ///
+    /// The first item of `stream` gets position `snapshot.global_pos`. The restored items are buffered like
+    /// items produced by `stream`, so only the newest `size` items are kept. A snapshot can't contain more than
+    /// `global_pos` items, so the oldest ones of an inconsistent snapshot are ignored.
+    pub fn restore(stream: T, size: usize, snapshot: BroadcastSnapshot<T::Item>) -> Self {
+        let mut state = Box::pin(StreamBroadcastState::new(stream, size));
+        let surplus = (snapshot.items.len() as u64).saturating_sub(snapshot.global_pos);
+        let start = snapshot.global_pos - (snapshot.items.len() as u64 - surplus);
+        *state.as_mut().project().global_pos = start;
+        for slot in snapshot.items.into_iter().skip(surplus as usize) {
+            state.as_mut().push_slot(slot);
+        }
+        let pos = state.start_pos();
+        Self::with_pos(Arc::new(Mutex::new(state)), pos)
+    }
+}
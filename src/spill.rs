@@ -0,0 +1,100 @@
+//! Spilling evicted items to disk, available with the `spill` feature
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// Converts items to bytes and back, to store them in a [spill](crate::BroadcastBuilder::spill) file
+pub trait SpillCodec<I>: Send {
+    /// Appends the encoded `item` to `buf`
+    fn encode(&mut self, item: &I, buf: &mut Vec<u8>);
+
+    /// Decodes an item, which was encoded by [encode](Self::encode)
+    fn decode(&mut self, bytes: &[u8]) -> std::io::Result<I>;
+}
+
+/// Items evicted from the cache, which are appended to a file
+///
+/// Position `first_pos + i` is stored at `index[i]`, where `None` is an item lost by the underlying stream.
+pub(crate) struct Spill<I> {
+    file: File,
+    codec: Box<dyn SpillCodec<I>>,
+    first_pos: u64,
+    /// Offset and length of each item in `file`
+    index: VecDeque<Option<(u64, usize)>>,
+    len: u64,
+    buf: Vec<u8>,
+}
+
+impl<I> Spill<I> {
+    pub(crate) fn new(file: File, codec: Box<dyn SpillCodec<I>>) -> Self {
+        Self {
+            file,
+            codec,
+            first_pos: 0,
+            index: Default::default(),
+            len: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Position after the last spilled item
+    fn end(&self) -> u64 {
+        self.first_pos + self.index.len() as u64
+    }
+
+    /// Appends the item at `pos`, which has to follow the last spilled item unless the spill is empty
+    pub(crate) fn append(&mut self, pos: u64, item: Option<&I>) -> std::io::Result<()> {
+        if self.index.is_empty() {
+            self.first_pos = pos;
+        }
+        debug_assert_eq!(pos, self.end(), "Spilled items are contiguous");
+        let Some(item) = item else {
+            self.index.push_back(None);
+            return Ok(());
+        };
+        self.buf.clear();
+        self.codec.encode(item, &mut self.buf);
+        let written = self
+            .file
+            .seek(SeekFrom::Start(self.len))
+            .and_then(|_| self.file.write_all(&self.buf));
+        // Items, which couldn't be written, are lost like the ones of the underlying stream
+        self.index
+            .push_back(written.is_ok().then_some((self.len, self.buf.len())));
+        self.len += self.buf.len() as u64;
+        written
+    }
+
+    /// Forgets all spilled items and reuses the file
+    pub(crate) fn clear(&mut self) -> std::io::Result<()> {
+        if !self.index.is_empty() {
+            self.index.clear();
+            self.len = 0;
+            self.file.set_len(0)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the first item at or after `pos`. Returns `None`, if `pos` isn't spilled.
+    ///
+    /// The returned position is the one of the item, which is `Err` if it couldn't be read.
+    pub(crate) fn read(&mut self, pos: u64) -> Option<(u64, std::io::Result<I>)> {
+        let start = pos.saturating_sub(self.first_pos);
+        let (offset, (at, len)) = self
+            .index
+            .iter()
+            .enumerate()
+            .skip(start as usize)
+            .find_map(|(i, entry)| entry.map(|entry| (i as u64, entry)))?;
+        self.buf.resize(len, 0);
+        let read = self
+            .file
+            .seek(SeekFrom::Start(at))
+            .and_then(|_| self.file.read_exact(&mut self.buf));
+        let item = read.and_then(|_| self.codec.decode(&self.buf));
+        Some((self.first_pos + offset, item))
+    }
+}
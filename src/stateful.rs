@@ -0,0 +1,102 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use super::{lock_state, StreamBroadcast};
+
+/// Item of [StatefulBroadcast]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateEvent<S, I> {
+    /// State folded from all items before the first [Delta](Self::Delta). This is the first event.
+    Snapshot(S),
+    /// Item with the number of skipped items before it, like the items of [StreamBroadcast]
+    Delta(u64, I),
+}
+
+/// Created by [broadcast_with_state](crate::StreamBroadcastExt::broadcast_with_state)
+///
+/// Each clone starts with a [StateEvent::Snapshot] of the state at the time it was cloned.
+#[pin_project]
+pub struct StatefulBroadcast<T: Stream, S> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    state: Arc<Mutex<S>>,
+    snapshot: Option<S>,
+}
+
+impl<T: Stream, S: Clone + Send + 'static> StatefulBroadcast<T, S>
+where
+    T::Item: Clone,
+{
+    pub(crate) fn new(
+        inner: StreamBroadcast<T>,
+        initial: S,
+        mut fold: impl FnMut(&mut S, &T::Item) + Send + 'static,
+    ) -> Self {
+        let state = Arc::new(Mutex::new(initial.clone()));
+        let folded = state.clone();
+        inner.tap(move |x| fold(&mut folded.lock().unwrap(), x));
+        Self {
+            inner,
+            state,
+            snapshot: Some(initial),
+        }
+    }
+}
+
+impl<T: Stream, S: Clone> StatefulBroadcast<T, S> {
+    /// State folded from all items, which entered the cache so far
+    pub fn state(&self) -> S {
+        self.state.lock().unwrap().clone()
+    }
+}
+
+impl<T: Stream, S: Clone> Clone for StatefulBroadcast<T, S> {
+    fn clone(&self) -> Self {
+        // Items are folded while the broadcast is locked, so the snapshot belongs to `pos`
+        let (pos, snapshot) = {
+            let lock = lock_state(&self.inner.state);
+            (lock.global_pos, self.state())
+        };
+        let mut inner = StreamBroadcast::with_pos(self.inner.state.clone(), pos);
+        inner.offset_mode = self.inner.offset_mode;
+        Self {
+            inner,
+            state: self.state.clone(),
+            snapshot: Some(snapshot),
+        }
+    }
+}
+
+impl<T: Stream, S> Stream for StatefulBroadcast<T, S>
+where
+    T::Item: Clone,
+{
+    type Item = StateEvent<S, T::Item>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Some(snapshot) = this.snapshot.take() {
+            return Poll::Ready(Some(StateEvent::Snapshot(snapshot)));
+        }
+        this.inner
+            .poll_next(cx)
+            .map(|x| x.map(|(skipped, x)| StateEvent::Delta(skipped, x)))
+    }
+}
+
+impl<T: Stream, S> FusedStream for StatefulBroadcast<T, S>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.snapshot.is_none() && self.inner.is_terminated()
+    }
+}
@@ -0,0 +1,26 @@
+/// Statistics about the shared cache, created by [stats](crate::StreamBroadcast::stats)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BroadcastStats {
+    /// Maximum number of buffered items
+    pub capacity: usize,
+    /// Number of currently buffered items
+    pub current_len: usize,
+    /// Largest distance between the newest item and the slowest subscriber ever observed.
+    /// Values above `capacity` mean that subscribers skipped items.
+    pub max_lag_observed: u64,
+    /// Sum of all items skipped by all subscribers
+    pub total_skips: u64,
+    /// Number of positions produced so far, which is the position of the next item
+    pub items_produced: u64,
+    /// Number of items evicted before every subscriber received them
+    pub items_dropped_unseen: u64,
+    /// Number of [StreamBroadcast](crate::StreamBroadcast)s
+    pub strong_count: usize,
+    /// Number of [WeakStreamBroadcast](crate::WeakStreamBroadcast)s
+    pub weak_count: usize,
+    /// Position of the next item of the slowest subscriber, or `None` without subscribers
+    pub min_subscriber_pos: Option<u64>,
+    /// Position of the next item of the fastest subscriber, or `None` without subscribers
+    pub max_subscriber_pos: Option<u64>,
+}
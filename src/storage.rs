@@ -0,0 +1,217 @@
+use std::{
+    ops::{Deref, Range},
+    sync::Arc,
+};
+
+/// Stores the buffered items of a broadcast, set with [storage](crate::BroadcastBuilder::storage)
+///
+/// The broadcast keeps track of the buffered positions and calls the methods in order: Positions are pushed
+/// one after another and the oldest one is evicted first. At most [capacity](Self::capacity) positions are
+/// stored at the same time. Items lost by the underlying stream are stored as `None`.
+pub trait BroadcastStorage<I> {
+    /// Maximum number of stored positions
+    fn capacity(&self) -> usize;
+
+    /// Stores `slot` at `pos`, which follows the previously pushed position
+    fn push(&mut self, pos: u64, slot: Option<I>);
+
+    /// Item at `pos`, which is `None` for a lost item or a position, which isn't stored
+    fn get(&self, pos: u64) -> Option<&I>;
+
+    /// Removes `pos`, which is the oldest stored position
+    fn evict(&mut self, pos: u64) -> Option<I>;
+
+    /// Changes the capacity, keeping the positions `stored`. `capacity` is at least the length of `stored`.
+    fn resize(&mut self, capacity: usize, stored: Range<u64>);
+}
+
+/// Default [BroadcastStorage], which is a ring buffer where position `p` is stored at `p % capacity`
+pub struct RingStorage<I> {
+    slots: Box<[Option<I>]>,
+}
+
+impl<I> RingStorage<I> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: std::iter::repeat_with(|| None).take(capacity).collect(),
+        }
+    }
+
+    fn index(&self, pos: u64) -> usize {
+        (pos % self.slots.len() as u64) as usize
+    }
+}
+
+impl<I> BroadcastStorage<I> for RingStorage<I> {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn push(&mut self, pos: u64, slot: Option<I>) {
+        self.slots[self.index(pos)] = slot;
+    }
+
+    fn get(&self, pos: u64) -> Option<&I> {
+        self.slots[self.index(pos)].as_ref()
+    }
+
+    fn evict(&mut self, pos: u64) -> Option<I> {
+        self.slots[self.index(pos)].take()
+    }
+
+    fn resize(&mut self, capacity: usize, stored: Range<u64>) {
+        let mut resized = Self::new(capacity);
+        for pos in stored {
+            resized.push(pos, self.evict(pos));
+        }
+        *self = resized;
+    }
+}
+
+/// Item taken from the cache, which can be cloned after the shared state is unlocked
+pub(crate) enum Slot<I> {
+    /// Item stored by [clone_unlocked](crate::BroadcastBuilder::clone_unlocked), which is cloned on demand
+    Shared(Arc<I>),
+    Owned(I),
+}
+
+impl<I> Deref for Slot<I> {
+    type Target = I;
+
+    fn deref(&self) -> &I {
+        match self {
+            Slot::Shared(item) => item,
+            Slot::Owned(item) => item,
+        }
+    }
+}
+
+impl<I> Slot<I> {
+    /// Returns the item, unless it's still shared
+    pub(crate) fn into_inner(self) -> Option<I> {
+        match self {
+            Slot::Shared(item) => Arc::into_inner(item),
+            Slot::Owned(item) => Some(item),
+        }
+    }
+}
+
+impl<I: Clone> Slot<I> {
+    /// Only clones the item, if the cache still holds it
+    pub(crate) fn into_owned(self) -> I {
+        match self {
+            Slot::Shared(item) => Arc::try_unwrap(item).unwrap_or_else(|item| I::clone(&item)),
+            Slot::Owned(item) => item,
+        }
+    }
+}
+
+/// Reads a buffered item while the shared state is locked
+pub(crate) trait ReadItem<I> {
+    type Output;
+
+    fn read(self, item: &I) -> Self::Output;
+
+    /// Reads an item stored by [clone_unlocked](crate::BroadcastBuilder::clone_unlocked), which might be shared
+    /// instead of read
+    fn read_shared(self, item: &Arc<I>) -> Self::Output
+    where
+        Self: Sized,
+    {
+        self.read(item)
+    }
+
+    /// Reads an item, which isn't buffered anymore
+    fn read_owned(self, item: I) -> Self::Output
+    where
+        Self: Sized,
+    {
+        self.read(&item)
+    }
+}
+
+impl<I, R, F: FnOnce(&I) -> R> ReadItem<I> for F {
+    type Output = R;
+
+    fn read(self, item: &I) -> R {
+        self(item)
+    }
+}
+
+/// Takes the item as [Slot], so it's cloned after the shared state is unlocked whenever possible
+pub(crate) struct Share;
+
+impl<I: Clone> ReadItem<I> for Share {
+    type Output = Slot<I>;
+
+    fn read(self, item: &I) -> Slot<I> {
+        Slot::Owned(item.clone())
+    }
+
+    fn read_shared(self, item: &Arc<I>) -> Slot<I> {
+        Slot::Shared(item.clone())
+    }
+
+    fn read_owned(self, item: I) -> Slot<I> {
+        Slot::Owned(item)
+    }
+}
+
+/// The default storage isn't boxed, so it doesn't require items to be `Send`
+pub(crate) enum Storage<I> {
+    Ring(RingStorage<I>),
+    /// Set by [BroadcastBuilder::clone_unlocked](crate::BroadcastBuilder::clone_unlocked). It's boxed, so only
+    /// broadcasts using it require the items to be `Sync`.
+    Shared(Box<dyn BroadcastStorage<Arc<I>> + Send>),
+    Custom(Box<dyn BroadcastStorage<I> + Send>),
+}
+
+impl<I> Storage<I> {
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            Storage::Ring(ring) => ring.capacity(),
+            Storage::Shared(shared) => shared.capacity(),
+            Storage::Custom(custom) => custom.capacity(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, pos: u64, slot: Option<I>) {
+        match self {
+            Storage::Ring(ring) => ring.push(pos, slot),
+            Storage::Shared(shared) => shared.push(pos, slot.map(Arc::new)),
+            Storage::Custom(custom) => custom.push(pos, slot),
+        }
+    }
+
+    pub(crate) fn get(&self, pos: u64) -> Option<&I> {
+        match self {
+            Storage::Ring(ring) => ring.get(pos),
+            Storage::Shared(shared) => shared.get(pos).map(|item| &**item),
+            Storage::Custom(custom) => custom.get(pos),
+        }
+    }
+
+    /// Passes the item at `pos` to `read`, see [get](Self::get)
+    pub(crate) fn read<R: ReadItem<I>>(&self, pos: u64, read: R) -> Option<R::Output> {
+        match self {
+            Storage::Shared(shared) => shared.get(pos).map(|item| read.read_shared(item)),
+            _ => self.get(pos).map(|item| read.read(item)),
+        }
+    }
+
+    pub(crate) fn evict(&mut self, pos: u64) -> Option<Slot<I>> {
+        match self {
+            Storage::Ring(ring) => ring.evict(pos).map(Slot::Owned),
+            Storage::Shared(shared) => shared.evict(pos).map(Slot::Shared),
+            Storage::Custom(custom) => custom.evict(pos).map(Slot::Owned),
+        }
+    }
+
+    pub(crate) fn resize(&mut self, capacity: usize, stored: Range<u64>) {
+        match self {
+            Storage::Ring(ring) => ring.resize(capacity, stored),
+            Storage::Shared(shared) => shared.resize(capacity, stored),
+            Storage::Custom(custom) => custom.resize(capacity, stored),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Deterministic stepping through the shared state, available with the `test-util` feature
+
+use futures::stream::Stream;
+use std::task::{Context, Poll};
+
+use super::{lock_state, StreamBroadcast};
+
+/// Copy of the shared state of a broadcast, created by [state_snapshot](crate::StreamBroadcast::state_snapshot)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StateSnapshot<I> {
+    /// Number of items received from the underlying stream
+    pub global_pos: u64,
+    /// Buffered items with their position, from oldest to newest
+    pub cached: Vec<(u64, I)>,
+    /// Number of broadcasts waiting to be woken up
+    pub waiting: usize,
+}
+
+impl<T: Stream> StreamBroadcast<T>
+where
+    T::Item: Clone,
+{
+    /// Polls the underlying stream exactly once, as if a subscriber at the newest position polled it
+    ///
+    /// Returns the new `global_pos` if an item was cached. The underlying stream is polled with a
+    /// no-op waker, so it is up to the test to call `advance` again once the stream is ready.
+    ///
+    /// ```
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    /// use std::task::Poll;
+    ///
+    /// let broadcast = futures::stream::iter(0..2).fuse().broadcast(5);
+    /// assert_eq!(Poll::Ready(Some(1)), broadcast.advance());
+    /// assert_eq!(vec![(0, 0)], broadcast.state_snapshot().cached);
+    /// assert_eq!(Poll::Ready(Some(2)), broadcast.advance());
+    /// assert_eq!(Poll::Ready(None), broadcast.advance());
+    /// ```
+    pub fn advance(&self) -> Poll<Option<u64>> {
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().poll_source(&mut cx, self.id)
+    }
+
+    /// Returns a copy of the shared state for assertions
+    pub fn state_snapshot(&self) -> StateSnapshot<T::Item> {
+        let mut lock = lock_state(&self.state);
+        lock.as_mut().expire();
+        StateSnapshot {
+            global_pos: lock.global_pos,
+            cached: lock.cached().map(|(pos, x)| (pos, x.clone())).collect(),
+            waiting: lock.wakable.len(),
+        }
+    }
+}
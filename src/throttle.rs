@@ -0,0 +1,76 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Poll},
+    time::Duration,
+};
+
+use super::StreamBroadcast;
+
+/// Created by [throttle](crate::StreamBroadcast::throttle)
+#[pin_project]
+pub struct ThrottledBroadcast<T: Stream, S, F> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    interval: Duration,
+    sleep: S,
+    /// Delay before the next item may be read
+    delay: Option<Pin<Box<F>>>,
+}
+
+impl<T: Stream, S, F> ThrottledBroadcast<T, S, F> {
+    pub(crate) fn new(inner: StreamBroadcast<T>, interval: Duration, sleep: S) -> Self {
+        Self {
+            inner,
+            interval,
+            sleep,
+            delay: None,
+        }
+    }
+
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream, S, F> Stream for ThrottledBroadcast<T, S, F>
+where
+    T::Item: Clone,
+    S: FnMut(Duration) -> F,
+    F: Future<Output = ()>,
+{
+    type Item = (u64, T::Item);
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if let Some(delay) = this.delay {
+            ready!(delay.as_mut().poll(cx));
+            *this.delay = None;
+        }
+        let item = ready!(this.inner.poll_next(cx));
+        if item.is_some() {
+            *this.delay = Some(Box::pin((this.sleep)(*this.interval)));
+        }
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Stream, S, F> FusedStream for ThrottledBroadcast<T, S, F>
+where
+    T::Item: Clone,
+    S: FnMut(Duration) -> F,
+    F: Future<Output = ()>,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
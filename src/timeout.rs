@@ -0,0 +1,79 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{future::Future, pin::Pin, task::Poll, time::Duration};
+
+use super::StreamBroadcast;
+
+/// Item of [TimeoutBroadcast]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeoutEvent<I> {
+    /// Item with the number of skipped items before it, like the items of [StreamBroadcast]
+    Item(u64, I),
+    /// No item arrived within the configured duration
+    Timeout,
+}
+
+/// Created by [inactivity_timeout](crate::StreamBroadcast::inactivity_timeout)
+#[pin_project]
+pub struct TimeoutBroadcast<T: Stream> {
+    #[pin]
+    inner: StreamBroadcast<T>,
+    duration: Duration,
+    /// Created on the first poll, so the first timeout starts then and no runtime is needed before
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<T: Stream> TimeoutBroadcast<T> {
+    pub(crate) fn new(inner: StreamBroadcast<T>, duration: Duration) -> Self {
+        Self {
+            inner,
+            duration,
+            sleep: None,
+        }
+    }
+
+    pub fn into_inner(self) -> StreamBroadcast<T> {
+        self.inner
+    }
+}
+
+impl<T: Stream> Stream for TimeoutBroadcast<T>
+where
+    T::Item: Clone,
+{
+    type Item = TimeoutEvent<T::Item>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let duration = *this.duration;
+        let deadline = tokio::time::Instant::now() + duration;
+        let sleep = this
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+        match this.inner.poll_next(cx) {
+            Poll::Ready(Some((skipped, x))) => {
+                sleep.as_mut().reset(deadline);
+                Poll::Ready(Some(TimeoutEvent::Item(skipped, x)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            // The broadcast registered the waker for the next item, so either of them wakes the task
+            Poll::Pending => {
+                futures::ready!(sleep.as_mut().poll(cx));
+                sleep.as_mut().reset(deadline);
+                Poll::Ready(Some(TimeoutEvent::Timeout))
+            }
+        }
+    }
+}
+
+impl<T: Stream> FusedStream for TimeoutBroadcast<T>
+where
+    T::Item: Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
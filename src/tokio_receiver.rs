@@ -0,0 +1,85 @@
+use futures::{
+    ready,
+    stream::{BoxStream, Fuse, FusedStream, Stream, StreamExt},
+};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::Poll,
+};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use super::{StreamBroadcast, StreamBroadcastState};
+
+/// Underlying stream of [from_tokio](StreamBroadcast::from_tokio)
+pub struct TokioReceiverSource<T> {
+    inner: Fuse<BoxStream<'static, Result<T, RecvError>>>,
+    /// Items lost by the receiver, which the broadcast reports as skipped
+    gaps: Arc<AtomicU64>,
+}
+
+impl<T: Clone + Send + 'static> StreamBroadcast<TokioReceiverSource<T>> {
+    /// Wraps a [tokio::sync::broadcast::Receiver], which allows to migrate to this crate step by step
+    ///
+    /// Items lost with [RecvError::Lagged] are reported as skipped, like the items evicted from the cache.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcast;
+    ///
+    /// let (tx, rx) = tokio::sync::broadcast::channel(2);
+    /// let broadcast = StreamBroadcast::from_tokio(rx, 5);
+    /// for i in 0..4 {
+    ///     tx.send(i).unwrap();
+    /// }
+    /// drop(tx);
+    /// assert_eq!(vec![(2, 2), (0, 3)], broadcast.collect::<Vec<_>>().await);
+    /// # }
+    /// ```
+    pub fn from_tokio(receiver: Receiver<T>, size: usize) -> Self {
+        let gaps = Arc::new(AtomicU64::new(0));
+        let inner = futures::stream::unfold(receiver, |mut receiver| async move {
+            match receiver.recv().await {
+                Err(RecvError::Closed) => None,
+                result => Some((result, receiver)),
+            }
+        });
+        let source = TokioReceiverSource {
+            inner: inner.boxed().fuse(),
+            gaps: gaps.clone(),
+        };
+        let mut state = StreamBroadcastState::new(source, size);
+        state.gaps = Some(gaps);
+        Self::from_state(state)
+    }
+}
+
+impl<T> Stream for TokioReceiverSource<T> {
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        loop {
+            match ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(x)) => return Poll::Ready(Some(x)),
+                Some(Err(RecvError::Lagged(n))) => {
+                    self.gaps.fetch_add(n, Ordering::SeqCst);
+                }
+                Some(Err(RecvError::Closed)) | None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T> FusedStream for TokioReceiverSource<T> {
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
@@ -0,0 +1,115 @@
+use futures::stream::{FusedStream, Stream};
+use pin_project::pin_project;
+use std::{pin::Pin, sync::Arc, task::Poll};
+
+use super::StreamBroadcast;
+
+/// Created by [try_broadcast](TryBroadcastExt::try_broadcast) and
+/// [try_broadcast_until_error](TryBroadcastExt::try_broadcast_until_error)
+pub type TryBroadcast<S> = StreamBroadcast<TryBroadcastSource<S>>;
+
+/// Broadcasts streams of results, whose errors don't implement [Clone]
+pub trait TryBroadcastExt<I, E>: Stream<Item = Result<I, E>> + Sized {
+    /// Like [broadcast](crate::StreamBroadcastExt::broadcast), but wraps errors in an [Arc]
+    ///
+    /// Errors are buffered like any other item, so every broadcast receives them.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::TryBroadcastExt;
+    ///
+    /// struct NotClone;
+    ///
+    /// let broadcast = futures::stream::iter([Ok(1), Err(NotClone), Ok(2)]).try_broadcast(3);
+    /// let broadcast2 = broadcast.clone();
+    /// assert_eq!(3, broadcast.count().await);
+    /// let items = broadcast2.map(|(_, x)| x.ok()).collect::<Vec<_>>().await;
+    /// assert_eq!(vec![Some(1), None, Some(2)], items);
+    /// # }
+    /// ```
+    fn try_broadcast(self, size: usize) -> TryBroadcast<Self>;
+
+    /// Like [try_broadcast](Self::try_broadcast), but terminates after the first error
+    ///
+    /// The error is still delivered to all broadcasts and the underlying stream isn't polled afterwards.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::TryBroadcastExt;
+    ///
+    /// let broadcast = futures::stream::iter([Ok(1), Err("failed"), Ok(2)]).try_broadcast_until_error(3);
+    /// let items = broadcast.map(|(_, x)| x.map_err(|e| *e)).collect::<Vec<_>>().await;
+    /// assert_eq!(vec![Ok(1), Err("failed")], items);
+    /// # }
+    /// ```
+    fn try_broadcast_until_error(self, size: usize) -> TryBroadcast<Self>;
+}
+
+impl<S, I: Clone, E> TryBroadcastExt<I, E> for S
+where
+    S: Stream<Item = Result<I, E>>,
+{
+    fn try_broadcast(self, size: usize) -> TryBroadcast<Self> {
+        StreamBroadcast::new(TryBroadcastSource::new(self, false), size)
+    }
+
+    fn try_broadcast_until_error(self, size: usize) -> TryBroadcast<Self> {
+        StreamBroadcast::new(TryBroadcastSource::new(self, true), size)
+    }
+}
+
+/// Underlying stream of a [TryBroadcast], which wraps the errors of `S` in an [Arc]
+#[pin_project]
+pub struct TryBroadcastSource<S> {
+    #[pin]
+    inner: S,
+    terminate_on_error: bool,
+    terminated: bool,
+}
+
+impl<S> TryBroadcastSource<S> {
+    fn new(inner: S, terminate_on_error: bool) -> Self {
+        Self {
+            inner,
+            terminate_on_error,
+            terminated: false,
+        }
+    }
+}
+
+impl<S, I, E> Stream for TryBroadcastSource<S>
+where
+    S: Stream<Item = Result<I, E>>,
+{
+    type Item = Result<I, Arc<E>>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if *this.terminated {
+            return Poll::Ready(None);
+        }
+        let x = futures::ready!(this.inner.poll_next(cx));
+        *this.terminated = match &x {
+            Some(Ok(_)) => false,
+            Some(Err(_)) => *this.terminate_on_error,
+            None => true,
+        };
+        Poll::Ready(x.map(|x| x.map_err(Arc::new)))
+    }
+}
+
+impl<S, I, E> FusedStream for TryBroadcastSource<S>
+where
+    S: Stream<Item = Result<I, E>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
@@ -1,57 +1,109 @@
 use futures::stream::{FusedStream, Stream};
-use pin_project::pin_project;
 use std::{
     ops::DerefMut,
-    pin::{pin, Pin},
+    pin::Pin,
     sync::{Mutex, Weak},
     task::Poll,
 };
 
-use super::{broadast_next, create_id, StreamBroadcast, StreamBroadcastState};
+use super::{
+    broadast_next, create_id, into_owned, lock_state, try_lock_state, StreamBroadcast,
+    StreamBroadcastState,
+};
 
 /// Created by [weak](crate::StreamBroadcast::weak)
-#[pin_project]
-pub struct WeakStreamBroadcast<T: FusedStream> {
+pub struct WeakStreamBroadcast<T: Stream> {
     pos: u64,
     id: u64,
     state: Weak<Mutex<Pin<Box<StreamBroadcastState<T>>>>>,
 }
 
-impl<T: FusedStream> WeakStreamBroadcast<T> {
+impl<T: Stream> Drop for WeakStreamBroadcast<T> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            if let Some(mut lock) = try_lock_state(&state) {
+                lock.as_mut().unsubscribe(self.id);
+            }
+        }
+    }
+}
+
+impl<T: Stream> WeakStreamBroadcast<T> {
     pub(crate) fn new(state: Weak<Mutex<Pin<Box<StreamBroadcastState<T>>>>>, pos: u64) -> Self {
-        Self {
-            pos,
-            id: create_id(),
-            state,
+        let id = create_id();
+        if let Some(state) = state.upgrade() {
+            lock_state(&state).as_mut().subscribe(id, pos, false);
         }
+        Self { pos, id, state }
     }
 
     /// Upgrades a WeakBroadcast to a StreamBroadcast, whose existence keeps the stream running
     pub fn upgrade(&self) -> Option<StreamBroadcast<T>> {
         let state = self.state.upgrade()?;
-        Some(StreamBroadcast {
-            pos: self.pos,
-            id: create_id(),
-            state,
+        Some(StreamBroadcast::with_pos(state, self.pos))
+    }
+
+    /// Like [upgrade](Self::upgrade), but skips items which are no longer buffered and returns their count
+    ///
+    /// This makes data loss explicit if the weak broadcast wasn't polled for a long time.
+    /// The returned [StreamBroadcast] starts at the oldest available item.
+    ///
+    /// ```
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// use futures::StreamExt;
+    /// use stream_broadcast::StreamBroadcastExt;
+    ///
+    /// let mut stream = futures::stream::iter(0..10).fuse().broadcast(3);
+    /// let weak = stream.downgrade();
+    /// (&mut stream).take(5).count().await;
+    /// let (mut upgraded, lost) = weak.upgrade_with_lag().unwrap();
+    /// assert_eq!(2, lost);
+    /// assert_eq!(Some((0, 2)), upgraded.next().await);
+    /// # }
+    /// ```
+    pub fn upgrade_with_lag(&self) -> Option<(StreamBroadcast<T>, u64)>
+    where
+        T::Item: Clone,
+    {
+        let state = self.state.upgrade()?;
+        let oldest = {
+            let mut lock = lock_state(&state);
+            lock.as_mut().expire();
+            lock.oldest_available()
+        };
+        let lost = oldest.saturating_sub(self.pos);
+        Some((StreamBroadcast::with_pos(state, self.pos.max(oldest)), lost))
+    }
+
+    /// Position of the next item to receive, like [StreamBroadcast::position]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Number of items produced by the underlying stream, which this broadcast didn't receive yet
+    ///
+    /// Returns 0, if all [StreamBroadcast]s were dropped.
+    pub fn lag(&self) -> u64 {
+        self.state.upgrade().map_or(0, |state| {
+            let global_pos = lock_state(&state).global_pos;
+            global_pos.saturating_sub(self.pos)
         })
     }
 }
 
-impl<T: FusedStream> Clone for WeakStreamBroadcast<T> {
+impl<T: Stream> Clone for WeakStreamBroadcast<T> {
     fn clone(&self) -> Self {
-        Self {
-            state: self.state.clone(),
-            id: create_id(),
-            pos: self
-                .state
-                .upgrade()
-                .map(|s| s.lock().unwrap().global_pos)
-                .unwrap_or(0), // State is never polled anyways
-        }
+        let pos = self
+            .state
+            .upgrade()
+            .map(|s| lock_state(&s).start_pos())
+            .unwrap_or(self.pos);
+        Self::new(self.state.clone(), pos)
     }
 }
 
-impl<T: FusedStream> Stream for WeakStreamBroadcast<T>
+impl<T: Stream> Stream for WeakStreamBroadcast<T>
 where
     T::Item: Clone,
 {
@@ -61,22 +113,24 @@ where
         self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        let this = self.project();
+        let this = self.get_mut();
         let Some(state) = this.state.upgrade() else {
             return Poll::Ready(None);
         };
-        let mut lock = state.lock().unwrap();
-        broadast_next(lock.deref_mut().as_mut(), cx, this.pos, *this.id)
+        let mut lock = lock_state(&state);
+        let polled = broadast_next(lock.deref_mut().as_mut(), cx, &mut this.pos, this.id);
+        drop(lock);
+        into_owned(polled)
     }
 }
 
-impl<T: FusedStream> FusedStream for WeakStreamBroadcast<T>
+impl<T: Stream> FusedStream for WeakStreamBroadcast<T>
 where
     T::Item: Clone,
 {
     fn is_terminated(&self) -> bool {
         if let Some(u) = self.state.upgrade() {
-            u.lock().unwrap().stream.is_terminated()
+            lock_state(&u).is_closed()
         } else {
             true
         }
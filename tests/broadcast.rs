@@ -1,6 +1,6 @@
 use std::{pin::pin, sync::atomic};
 
-use futures::{Stream, StreamExt};
+use futures::{stream::FusedStream, Stream, StreamExt};
 use stream_broadcast::{StreamBroadcast, StreamBroadcastExt};
 
 #[tokio::test]
@@ -116,3 +116,2171 @@ async fn weak_terminates_when_all_owned_are_destroyed() {
     drop(stream2);
     assert_eq!(None, weak.next().await);
 }
+
+#[tokio::test]
+async fn try_next_now_only_returns_buffered_items() {
+    let mut broadcast = futures::stream::iter(0..2)
+        .chain(futures::stream::poll_fn(
+            |_| -> std::task::Poll<Option<i32>> {
+                panic!("try_next_now must not poll the inner stream")
+            },
+        ))
+        .fuse()
+        .broadcast(3);
+    let mut broadcast2 = broadcast.clone();
+    assert_eq!(None, broadcast2.try_next_now());
+
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert_eq!(Some(Some((0, 0))), broadcast2.try_next_now());
+    assert_eq!(Some(Some((0, 1))), broadcast2.try_next_now());
+    assert_eq!(None, broadcast2.try_next_now());
+    assert_eq!(None, broadcast.try_next_now());
+}
+
+#[tokio::test]
+async fn try_next_now_reports_termination() {
+    let mut broadcast = futures::stream::iter(0..1).fuse().broadcast(3);
+    let mut broadcast2 = broadcast.clone();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(None, broadcast.next().await);
+    assert_eq!(Some(Some((0, 0))), broadcast2.try_next_now());
+    assert_eq!(Some(None), broadcast2.try_next_now());
+}
+
+#[tokio::test]
+async fn cache_holds_exactly_size_items() {
+    for size in 1..10 {
+        let broadcast = futures::stream::iter(0..20).fuse().broadcast(size);
+        let lagging = broadcast.clone();
+        assert_eq!(20, broadcast.count().await);
+
+        let expected = std::iter::once((20 - size as u64, 20 - size as i32))
+            .chain((21 - size as i32..20).map(|x| (0, x)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, lagging.collect::<Vec<_>>().await);
+    }
+}
+
+#[tokio::test]
+async fn with_latest_borrows_newest_item() {
+    let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let lagging = broadcast.clone();
+    assert_eq!(None, broadcast.with_latest(|x| x.copied()));
+    while broadcast.next().await.is_some() {
+        let latest = broadcast.with_latest(|x| x.copied());
+        assert_eq!(latest, lagging.with_latest(|x| x.copied()));
+    }
+    assert_eq!(Some(4), lagging.with_latest(|x| x.copied()));
+}
+
+#[tokio::test]
+async fn upgrade_with_lag_reports_items_lost_while_downgraded() {
+    let mut stream = futures::stream::iter(0..20).fuse().broadcast(3);
+    let weak = stream.downgrade();
+
+    let (_, lost) = weak.upgrade_with_lag().unwrap();
+    assert_eq!(0, lost);
+
+    assert_eq!(15, (&mut stream).take(15).count().await);
+    let (upgraded, lost) = weak.upgrade_with_lag().unwrap();
+    assert_eq!(12, lost);
+    assert_eq!(
+        vec![(0, 12), (0, 13), (0, 14), (0, 15), (0, 16)],
+        upgraded.take(5).collect::<Vec<_>>().await
+    );
+
+    drop(stream);
+    assert!(weak.upgrade_with_lag().is_none());
+}
+
+#[tokio::test]
+async fn broadcast_inspect_is_called_once_per_produced_item() {
+    let inspected = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let inspected2 = inspected.clone();
+    let broadcast = futures::stream::iter(0..5)
+        .fuse()
+        .broadcast_inspect(2, move |x| inspected2.lock().unwrap().push(*x));
+    let lagging = broadcast.clone();
+    let lagging2 = broadcast.clone();
+
+    assert_eq!(5, broadcast.count().await);
+    assert_eq!(2, lagging.count().await);
+    assert_eq!(2, lagging2.count().await);
+    assert_eq!(vec![0, 1, 2, 3, 4], *inspected.lock().unwrap());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn next_timeout_does_not_consume_items() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast(3);
+    let timeout = std::time::Duration::from_millis(10);
+    assert!(broadcast.next_timeout(timeout).await.is_err());
+    assert!(broadcast.next_timeout(timeout).await.is_err());
+
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(Ok(Some((0, 1))), broadcast.next_timeout(timeout).await);
+    drop(tx);
+    assert_eq!(Ok(None), broadcast.next_timeout(timeout).await);
+}
+
+#[tokio::test]
+async fn fallible_works_with_try_stream_combinators() {
+    use futures::TryStreamExt;
+    use stream_broadcast::BroadcastError;
+
+    let broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let fallible = broadcast.clone().fallible();
+    let mut lagging = broadcast.clone().fallible();
+    assert_eq!(5, broadcast.count().await);
+
+    assert_eq!(
+        Err(BroadcastError::Lagged { skipped: 3 }),
+        fallible.try_collect::<Vec<_>>().await
+    );
+    assert_eq!(
+        Some(Err(BroadcastError::Lagged { skipped: 3 })),
+        lagging.next().await
+    );
+    assert_eq!(Ok(vec![3, 4]), lagging.try_collect::<Vec<_>>().await);
+    assert_eq!(
+        "broadcast lagged behind and skipped 3 items",
+        BroadcastError::Lagged { skipped: 3 }.to_string()
+    );
+}
+
+#[tokio::test]
+async fn pump_drives_stream_without_subscribers_polling() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast(3);
+    let mut slow = broadcast.clone();
+    let pump = tokio::spawn(broadcast.pump());
+    let mut subscriber = broadcast.clone();
+
+    for i in 0..5 {
+        tx.unbounded_send(i).unwrap();
+        assert_eq!(Some((0, i)), subscriber.next().await);
+    }
+    drop(broadcast);
+    drop(subscriber);
+    tx.unbounded_send(5).unwrap();
+    drop(tx);
+    pump.await.unwrap();
+
+    assert_eq!(
+        vec![(3, 3), (0, 4), (0, 5)],
+        (&mut slow).collect::<Vec<_>>().await
+    );
+    assert!(slow.is_terminated());
+}
+
+#[tokio::test]
+async fn stats_track_lag_and_skips() {
+    let broadcast = futures::stream::iter(0..10).fuse().broadcast(4);
+    let mut lagging = broadcast.clone();
+    let stats = broadcast.stats();
+    assert_eq!(
+        (4, 0, 0, 0),
+        (
+            stats.capacity,
+            stats.current_len,
+            stats.max_lag_observed,
+            stats.total_skips
+        )
+    );
+
+    assert_eq!(10, broadcast.count().await);
+    assert_eq!(4, (&mut lagging).count().await);
+    let stats = lagging.stats();
+    assert_eq!(
+        (4, 4, 10, 6),
+        (
+            stats.capacity,
+            stats.current_len,
+            stats.max_lag_observed,
+            stats.total_skips
+        )
+    );
+
+    // Dropped subscribers don't count as lagging anymore
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(4);
+    drop(broadcast.clone());
+    assert_eq!(10, (&mut broadcast).count().await);
+    assert_eq!(1, broadcast.stats().max_lag_observed);
+}
+
+#[tokio::test]
+async fn clone_weak_while_state_is_dropped() {
+    for _ in 0..100 {
+        let mut stream = futures::stream::iter(0..10).fuse().broadcast(3);
+        (&mut stream).take(5).count().await;
+        let weak = stream.downgrade();
+        let dropper = std::thread::spawn(move || drop(stream));
+        let cloned = weak.clone();
+        dropper.join().unwrap();
+        assert!(cloned.upgrade().is_none());
+        assert_eq!(None, pin!(cloned).next().await);
+    }
+}
+
+#[tokio::test]
+async fn merge_positions_returns_commonly_covered_position() {
+    let mut a = futures::stream::iter(0..10).fuse().broadcast(3);
+    let mut b = a.clone();
+    assert_eq!(Some(0), a.merge_positions(&b));
+
+    (&mut a).take(8).count().await;
+    assert_eq!(Some(0), a.merge_positions(&b));
+    assert_eq!(Some((5, 5)), b.next().await);
+    assert_eq!(Some(6), b.merge_positions(&a));
+
+    let other = futures::stream::iter(0..10).fuse().broadcast(3);
+    assert_eq!(None, a.merge_positions(&other));
+}
+
+#[tokio::test]
+async fn overflow_sink_receives_unread_evicted_items() {
+    let evicted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let evicted2 = evicted.clone();
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    broadcast.set_overflow_sink(move |x| evicted2.lock().unwrap().push(x));
+
+    // Items read by every subscriber are not reported
+    assert_eq!(5, (&mut broadcast).take(5).count().await);
+    assert!(evicted.lock().unwrap().is_empty());
+
+    let lagging = broadcast.clone();
+    assert_eq!(5, broadcast.count().await);
+    assert_eq!(vec![5, 6], *evicted.lock().unwrap());
+    assert_eq!(3, lagging.count().await);
+}
+
+#[tokio::test]
+async fn cursors_track_their_own_position() {
+    let broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let mut fast = broadcast.cursor();
+    let mut slow = broadcast.cursor();
+    assert_eq!(Some((0, 0)), fast.next().await);
+    assert_eq!(Some((0, 1)), fast.next().await);
+    assert_eq!(Some((0, 0)), slow.next().await);
+    assert_eq!(3, fast.count().await);
+    assert_eq!(vec![(2, 3), (0, 4)], slow.collect::<Vec<_>>().await);
+    assert_eq!(2, broadcast.stats().total_skips);
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn advance_steps_through_the_state_machine() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast(2);
+    let mut subscriber = broadcast.clone();
+    assert_eq!(std::task::Poll::Pending, broadcast.advance());
+    assert_eq!(std::task::Poll::Pending, futures::poll!(subscriber.next()));
+    assert_eq!(1, broadcast.state_snapshot().waiting);
+
+    for i in 0..3 {
+        tx.unbounded_send(i).unwrap();
+        assert_eq!(
+            std::task::Poll::Ready(Some(i as u64 + 1)),
+            broadcast.advance()
+        );
+    }
+    let snapshot = broadcast.state_snapshot();
+    assert_eq!(
+        (3, vec![(1, 1), (2, 2)], 0),
+        (snapshot.global_pos, snapshot.cached, snapshot.waiting)
+    );
+    assert_eq!(Some((1, 1)), subscriber.next().await);
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn bytes_broadcast_fails_lagging_writers() {
+    use bytes::Bytes;
+    use stream_broadcast::BytesBroadcastExt;
+
+    let chunks = (0..10u8).map(|i| Bytes::from(vec![i; 3]));
+    let mut broadcast = futures::stream::iter(chunks).fuse().broadcast_bytes(4);
+    let lagging = broadcast.clone();
+    let mut skipping = broadcast.clone();
+    assert_eq!(0, lagging.lag());
+    assert_eq!(Some(Bytes::from(vec![0; 3])), broadcast.next().await);
+    assert_eq!(1, lagging.lag());
+
+    let mut written = Vec::new();
+    assert_eq!(27, broadcast.copy_to(&mut written).await.unwrap());
+    assert_eq!(27, written.len());
+
+    let error = lagging.copy_to(Vec::new()).await.unwrap_err();
+    assert_eq!(
+        "broadcast lagged behind and skipped 6 items",
+        error.to_string()
+    );
+    assert_eq!(4, (&mut skipping).count().await);
+    assert_eq!(6, skipping.skipped());
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn coalesced_bytes_bound_the_cache_in_bytes() {
+    use bytes::Bytes;
+    use stream_broadcast::BytesBroadcastExt;
+
+    let chunks = (0..12u8).map(|i| Bytes::from(vec![i; 3]));
+    let broadcast = futures::stream::iter(chunks).broadcast_bytes_coalesced(18, 8);
+    let mut lagging = broadcast.clone();
+    let frames = broadcast.collect::<Vec<_>>().await;
+    assert_eq!(
+        vec![9; 4],
+        frames.iter().map(Bytes::len).collect::<Vec<_>>()
+    );
+    assert_eq!(&[0, 0, 0, 1, 1, 1, 2, 2, 2][..], &frames[0][..]);
+
+    assert_eq!(frames[2..], (&mut lagging).collect::<Vec<_>>().await[..]);
+    assert_eq!(2, lagging.skipped());
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn read_broadcast_shares_the_read_ahead_window() {
+    use futures::AsyncReadExt;
+    use stream_broadcast::AsyncReadBroadcastExt;
+
+    let mut reader = futures::io::Cursor::new(b"abcdefgh").broadcast_read(3, 2);
+    let mut lagging = reader.clone();
+    let mut head = [0; 2];
+    reader.read_exact(&mut head).await.unwrap();
+    let mut continued = reader.clone();
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).await.unwrap();
+    assert_eq!(b"cdefgh", &rest[..]);
+    rest.clear();
+    continued.read_to_end(&mut rest).await.unwrap();
+    assert_eq!(b"cdefgh", &rest[..]);
+
+    let error = lagging.read(&mut head).await.unwrap_err();
+    assert_eq!(
+        "broadcast lagged behind and skipped 1 items",
+        error.to_string()
+    );
+}
+
+#[tokio::test]
+async fn errors_are_only_received_by_the_poller() {
+    use stream_broadcast::ErrorDelivery;
+
+    let mut a = futures::stream::iter([Ok(0), Err("first"), Ok(1), Err("second")])
+        .fuse()
+        .broadcast_builder()
+        .capacity(1)
+        .error_delivery(ErrorDelivery::Poller)
+        .build();
+    let mut b = a.clone();
+    assert_eq!(Some((0, Ok(0))), a.next().await);
+    assert_eq!(Some((0, Ok(0))), b.next().await);
+    assert_eq!(Some((0, Err("first"))), b.next().await);
+    assert_eq!(Some((0, Ok(1))), a.next().await);
+    assert_eq!(Some((0, Err("second"))), a.next().await);
+    assert_eq!(vec![(0, Ok(1))], b.collect::<Vec<_>>().await);
+    assert_eq!(None, a.next().await);
+}
+
+#[cfg(feature = "recorder")]
+#[tokio::test]
+async fn recorder_keeps_evicted_items_and_their_consumers() {
+    use stream_broadcast::Recorder;
+
+    let recorder = Recorder::new();
+    let mut fast = futures::stream::iter(0..4)
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .record(&recorder)
+        .build();
+    let mut slow = fast.clone();
+    assert_eq!(4, (&mut fast).count().await);
+    assert_eq!(vec![(2, 2), (0, 3)], (&mut slow).collect::<Vec<_>>().await);
+
+    assert_eq!(4, recorder.len());
+    let consumers = |pos| {
+        recorder
+            .get(pos)
+            .unwrap()
+            .consumed
+            .iter()
+            .map(|c| c.subscriber)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(vec![fast.subscriber_id()], consumers(0));
+    assert_eq!(vec![fast.subscriber_id()], consumers(1));
+    assert_eq!(
+        vec![fast.subscriber_id(), slow.subscriber_id()],
+        consumers(3)
+    );
+    let items = recorder.range(1..3).into_iter().map(|r| r.item);
+    assert_eq!(vec![Some(1), Some(2)], items.collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn cumulative_offsets_sum_up_skipped_items() {
+    use stream_broadcast::OffsetMode;
+
+    let mut broadcast = futures::stream::iter(0..20).fuse().broadcast(3);
+    let mut cumulative = broadcast.clone();
+    cumulative.set_offset_mode(OffsetMode::Cumulative);
+    let mut inherited = cumulative.clone();
+
+    (&mut broadcast).take(10).count().await;
+    assert_eq!(Some((7, 7)), cumulative.next().await);
+    assert_eq!(Some(Some((7, 8))), cumulative.try_next_now());
+    assert_eq!(Some((7, 7)), inherited.next().await);
+
+    broadcast.count().await;
+    assert_eq!(Some((15, 17)), cumulative.next().await);
+    assert_eq!(Some((15, 18)), cumulative.next().await);
+}
+
+#[tokio::test]
+async fn close_after_stops_at_position() {
+    let mut broadcast = futures::stream::iter(0..20).fuse().broadcast(3);
+    let mut without_lag = broadcast.clone();
+    without_lag.close_after(2);
+    let mut with_lag = broadcast.clone();
+    with_lag.close_after(8);
+    let mut skipped_past_end = broadcast.clone();
+    skipped_past_end.close_after(4);
+
+    assert_eq!(
+        vec![(0, 0), (0, 1)],
+        (&mut without_lag).collect::<Vec<_>>().await
+    );
+    assert!(without_lag.is_terminated());
+
+    (&mut broadcast).take(10).count().await;
+    assert_eq!(vec![(7, 7)], with_lag.collect::<Vec<_>>().await);
+    assert_eq!(Some(None), skipped_past_end.try_next_now());
+    assert_eq!(None, skipped_past_end.next().await);
+}
+
+#[tokio::test]
+async fn backpressure_never_skips_items() {
+    const ITERATIONS: usize = 50;
+    let fast = futures::stream::iter(0..ITERATIONS)
+        .fuse()
+        .broadcast_with_backpressure(3);
+    let slow = fast.clone().then(|x| async move {
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        x
+    });
+    let weak = fast.downgrade();
+
+    let (fast, slow) = futures::future::join(
+        tokio::task::spawn(fast.collect::<Vec<_>>()),
+        tokio::task::spawn(slow.collect::<Vec<_>>()),
+    )
+    .await;
+    let expected = (0..ITERATIONS).map(|x| (0, x)).collect::<Vec<_>>();
+    assert_eq!(expected, fast.unwrap());
+    assert_eq!(expected, slow.unwrap());
+    drop(weak);
+}
+
+#[tokio::test]
+async fn backpressure_resumes_when_laggard_is_dropped() {
+    let mut fast = futures::stream::iter(0..5)
+        .fuse()
+        .broadcast_with_backpressure(2);
+    let slow = fast.clone();
+    assert_eq!(2, (&mut fast).take(2).count().await);
+    assert!(futures::poll!(fast.next()).is_pending());
+    drop(slow);
+    assert_eq!(3, fast.count().await);
+}
+
+#[tokio::test]
+async fn events_report_lag_and_close_explicitly() {
+    use stream_broadcast::BroadcastEvent;
+
+    let broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let events = broadcast.clone().into_events();
+    assert_eq!(5, broadcast.count().await);
+    assert_eq!(
+        vec![
+            BroadcastEvent::Lagged(3),
+            BroadcastEvent::Item(3),
+            BroadcastEvent::Item(4),
+            BroadcastEvent::Closed
+        ],
+        events.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn items_count_skipped_items() {
+    let broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let mut items = broadcast.clone().items();
+    assert_eq!(5, broadcast.count().await);
+    assert_eq!(vec![3, 4], (&mut items).collect::<Vec<_>>().await);
+    assert_eq!(3, items.skipped());
+}
+
+#[tokio::test]
+async fn builder_configures_start_and_lag_policy() {
+    use stream_broadcast::{LagPolicy, StartPos};
+
+    let mut oldest = futures::stream::iter(0..10)
+        .fuse()
+        .broadcast_builder()
+        .capacity(3)
+        .start_at(StartPos::Oldest)
+        .build();
+    assert_eq!(5, (&mut oldest).take(5).count().await);
+    assert_eq!(Some((0, 2)), oldest.clone().next().await);
+    assert_eq!(Some((0, 2)), oldest.cursor().next().await);
+
+    let mut backpressure = futures::stream::iter(0..10)
+        .fuse()
+        .broadcast_builder()
+        .capacity(3)
+        .lag_policy(LagPolicy::Backpressure)
+        .build();
+    let _slow = backpressure.clone();
+    assert_eq!(3, (&mut backpressure).take(3).count().await);
+    assert!(futures::poll!(backpressure.next()).is_pending());
+}
+
+#[tokio::test]
+async fn expired_items_are_skipped() {
+    let mut broadcast = futures::stream::iter(0..5)
+        .fuse()
+        .broadcast_builder()
+        .capacity(10)
+        .ttl(std::time::Duration::from_millis(50))
+        .build();
+    let mut lagging = broadcast.clone();
+    let mut in_time = broadcast.clone();
+    assert_eq!(2, (&mut broadcast).take(2).count().await);
+    assert_eq!(Some((0, 0)), in_time.next().await);
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(Some((0, 2)), broadcast.next().await);
+    assert_eq!(Some((2, 2)), lagging.next().await);
+    assert_eq!(Some((1, 2)), in_time.next().await);
+}
+
+#[tokio::test]
+async fn weighted_cache_grows_within_budget() {
+    let mut broadcast = futures::stream::iter(0..10)
+        .fuse()
+        .broadcast_builder()
+        .capacity(1)
+        .max_weight(6, |x| if *x == 5 { 4 } else { 1 })
+        .build();
+    let mut slow = broadcast.clone();
+    let lagging = broadcast.clone();
+    assert_eq!(5, (&mut broadcast).take(5).count().await);
+    assert_eq!(
+        vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+        (&mut slow).take(5).collect::<Vec<_>>().await
+    );
+    assert_eq!(5, broadcast.count().await);
+    assert_eq!(
+        vec![(6, 6), (0, 7), (0, 8), (0, 9)],
+        lagging.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn growing_capacity_releases_backpressure() {
+    let mut fast = futures::stream::iter(0..10)
+        .fuse()
+        .broadcast_with_backpressure(2);
+    let slow = fast.clone();
+    assert_eq!(2, (&mut fast).take(2).count().await);
+    assert!(futures::poll!(fast.next()).is_pending());
+    slow.set_capacity(4);
+    assert_eq!(2, (&mut fast).take(2).count().await);
+    assert_eq!(
+        vec![(0, 0), (0, 1), (0, 2), (0, 3)],
+        slow.take(4).collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn retain_all_keeps_full_history() {
+    let mut broadcast = futures::stream::iter(0..100)
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .retain_all()
+        .build();
+    assert_eq!(100, (&mut broadcast).count().await);
+    let late = broadcast.resubscribe_from_start();
+    assert_eq!(
+        (0..100).map(|x| (0, x)).collect::<Vec<_>>(),
+        late.collect::<Vec<_>>().await
+    );
+    assert_eq!(100, broadcast.stats().current_len);
+}
+
+#[tokio::test]
+async fn subscribe_at_validates_buffered_window() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    assert_eq!(5, (&mut broadcast).take(5).count().await);
+    let Err(err) = broadcast.subscribe_at(6) else {
+        panic!("position 6 isn't produced yet");
+    };
+    assert_eq!(6, err.pos);
+    assert_eq!(2..=5, err.available);
+    let Ok(resumed) = broadcast.subscribe_at(2) else {
+        panic!("position 2 is buffered");
+    };
+    assert_eq!(
+        vec![(0, 2), (0, 3), (0, 4), (0, 5)],
+        resumed.take(4).collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn latest_only_yields_newer_items() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast_latest();
+    tx.unbounded_send(1).unwrap();
+    tx.unbounded_send(2).unwrap();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert_eq!(Some((0, 2)), broadcast.next().await);
+
+    let mut late = broadcast.clone();
+    assert_eq!(Some((0, 2)), late.next().await);
+    assert!(futures::poll!(late.next()).is_pending());
+    tx.unbounded_send(3).unwrap();
+    assert_eq!(Some((0, 3)), late.next().await);
+}
+
+#[tokio::test]
+async fn conflated_broadcast_jumps_to_newest() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(5);
+    let mut ui = broadcast.clone();
+    ui.conflate();
+    assert_eq!(3, (&mut broadcast).take(3).count().await);
+    assert_eq!(Some(Some((2, 2))), ui.try_next_now());
+    assert_eq!(Some((0, 3)), ui.next().await);
+    assert_eq!(4, (&mut broadcast).take(4).count().await);
+    assert_eq!(Some((2, 6)), ui.next().await);
+    assert_eq!(4, ui.stats().total_skips);
+}
+
+#[tokio::test]
+async fn filtered_subscription_waits_for_matching_item() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast(2);
+    let mut filtered = broadcast.subscribe_filter(|x: &i32| *x > 10);
+    tx.unbounded_send(1).unwrap();
+    tx.unbounded_send(2).unwrap();
+    assert!(futures::poll!(filtered.next()).is_pending());
+    let handle = tokio::spawn(async move { filtered.next().await });
+    tx.unbounded_send(11).unwrap();
+    assert_eq!(Some((0, 11)), handle.await.unwrap());
+    drop(broadcast);
+}
+
+#[tokio::test]
+async fn projection_doesnt_clone_items() {
+    #[derive(Debug)]
+    struct Large {
+        id: u32,
+        clones: std::sync::Arc<atomic::AtomicUsize>,
+    }
+    impl Clone for Large {
+        fn clone(&self) -> Self {
+            self.clones.fetch_add(1, atomic::Ordering::SeqCst);
+            Self {
+                id: self.id,
+                clones: self.clones.clone(),
+            }
+        }
+    }
+
+    let clones = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+    let source_clones = clones.clone();
+    let broadcast = futures::stream::iter(0..3)
+        .map(move |id| Large {
+            id,
+            clones: source_clones.clone(),
+        })
+        .fuse()
+        .broadcast(2);
+    let ids = broadcast.subscribe_with(|x| x.id);
+    assert_eq!(vec![(0, 0), (0, 1), (0, 2)], ids.collect::<Vec<_>>().await);
+    assert_eq!(0, clones.load(atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn keyed_subscriptions_lag_independently() {
+    let keyed = futures::stream::iter(0..20)
+        .fuse()
+        .broadcast_by_key(2, |x| x % 2);
+    let mut even = keyed.subscribe_key(0);
+    let mut odd = keyed.subscribe_key(1);
+    assert_eq!(Some((0, 0)), even.next().await);
+    assert_eq!(Some((0, 2)), even.next().await);
+    assert_eq!(1, odd.lag());
+    assert_eq!(Some((0, 1)), odd.next().await);
+    assert_eq!(
+        vec![
+            (0, 4),
+            (0, 6),
+            (0, 8),
+            (0, 10),
+            (0, 12),
+            (0, 14),
+            (0, 16),
+            (0, 18)
+        ],
+        (&mut even).collect::<Vec<_>>().await
+    );
+    assert_eq!(Some((7, 17)), odd.next().await);
+    assert_eq!(vec![(0, 19)], odd.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn distributed_items_are_received_once() {
+    let first = futures::stream::iter(0..100).fuse().distribute(4);
+    let handles = (0..4)
+        .map(|_| tokio::spawn(first.clone().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    drop(first);
+    let mut received = Vec::new();
+    for handle in handles {
+        received.extend(handle.await.unwrap());
+    }
+    received.sort();
+    assert_eq!((0..100).map(|x| (0, x)).collect::<Vec<_>>(), received);
+}
+
+#[tokio::test]
+async fn round_robin_assigns_items_by_position() {
+    let mut fast = futures::stream::iter(0..9).fuse().round_robin(9);
+    let slow = fast.clone();
+    let third = fast.clone();
+    assert_eq!(
+        vec![(0, 0), (0, 3), (0, 6)],
+        (&mut fast).collect::<Vec<_>>().await
+    );
+    assert_eq!(
+        vec![(0, 2), (0, 5), (0, 8)],
+        third.collect::<Vec<_>>().await
+    );
+    assert_eq!(vec![(0, 1), (0, 4), (0, 7)], slow.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn channel_wakes_waiting_receivers() {
+    let (sender, receiver) = stream_broadcast::channel::<u32>(2);
+    let handles = (0..2)
+        .map(|_| tokio::spawn(receiver.clone().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    drop(receiver);
+    tokio::task::yield_now().await;
+    let sender2 = sender.clone();
+    sender.push(1);
+    drop(sender);
+    sender2.push(2);
+    drop(sender2);
+    for handle in handles {
+        assert_eq!(vec![(0, 1), (0, 2)], handle.await.unwrap());
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn tokio_receiver_lag_is_reported_as_skipped() {
+    let (tx, rx) = tokio::sync::broadcast::channel(2);
+    let mut broadcast = stream_broadcast::StreamBroadcast::from_tokio(rx, 3);
+    let mut lagging = broadcast.clone();
+    tx.send(0).unwrap();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    for i in 1..5 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(Some((2, 3)), broadcast.next().await);
+    assert_eq!(Some((0, 4)), broadcast.next().await);
+    assert_eq!(
+        vec![(3, 3), (0, 4)],
+        (&mut lagging).take(2).collect::<Vec<_>>().await
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn driven_broadcast_progresses_without_subscribers_polling() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast_driven(2);
+    let mut paused = broadcast.clone();
+    for i in 0..4 {
+        tx.unbounded_send(i).unwrap();
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    assert_eq!(Some(Some((2, 2))), paused.try_next_now());
+    drop(tx);
+    assert_eq!(vec![(2, 2), (0, 3)], broadcast.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn buffer_introspection_follows_eviction() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(4);
+    assert_eq!(0, broadcast.buffered_len());
+    assert_eq!(0, broadcast.oldest_available_pos());
+    assert_eq!(10, (&mut broadcast).count().await);
+    assert_eq!(4, broadcast.buffered_len());
+    assert_eq!(4, broadcast.capacity());
+    assert_eq!(6, broadcast.oldest_available_pos());
+    broadcast.set_capacity(2);
+    assert_eq!(2, broadcast.buffered_len());
+    assert_eq!(8, broadcast.oldest_available_pos());
+}
+
+#[tokio::test]
+async fn subscriber_counts_track_handles() {
+    let broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    let weak = broadcast.downgrade();
+    {
+        let _cursor = broadcast.cursor();
+        let _items = broadcast.clone().items();
+        assert_eq!(3, broadcast.subscriber_count());
+    }
+    assert_eq!(1, broadcast.subscriber_count());
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(2, broadcast.subscriber_count());
+    assert_eq!(1, upgraded.weak_count());
+    drop(weak);
+    assert_eq!(0, upgraded.weak_count());
+}
+
+#[tokio::test]
+async fn lag_is_reported_before_items_are_skipped() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    let mut weak = broadcast.downgrade();
+    assert_eq!(3, (&mut broadcast).take(3).count().await);
+    assert_eq!(3, weak.lag());
+    assert_eq!(Some((0, 0)), weak.next().await);
+    assert_eq!(2, weak.lag());
+    drop(broadcast);
+    assert_eq!(0, weak.lag());
+}
+
+#[tokio::test]
+async fn position_includes_skipped_items() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    let mut slow = broadcast.downgrade();
+    assert_eq!(0, slow.position());
+    assert_eq!(6, (&mut broadcast).take(6).count().await);
+    assert_eq!(6, broadcast.position());
+    assert_eq!(Some((3, 3)), slow.next().await);
+    assert_eq!(4, slow.position());
+}
+
+#[tokio::test]
+async fn close_wakes_waiting_broadcasts() {
+    let (_tx, rx) = futures::channel::mpsc::channel::<i32>(1);
+    let broadcast = rx.broadcast(3);
+    let waiting = tokio::spawn(broadcast.clone().collect::<Vec<_>>());
+    tokio::task::yield_now().await;
+    broadcast.close();
+    assert_eq!(Vec::<(u64, i32)>::new(), waiting.await.unwrap());
+    assert!(broadcast.is_terminated());
+}
+
+#[tokio::test]
+async fn into_inner_fails_while_a_sender_exists() {
+    let (sender, receiver) = stream_broadcast::channel::<i32>(3);
+    let receiver = receiver.into_inner().err().unwrap();
+    drop(sender);
+    assert!(receiver.into_inner().ok().unwrap().is_terminated());
+}
+
+#[tokio::test]
+async fn size_hint_counts_buffered_items_and_upstream() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(5);
+    let slow = broadcast.clone();
+    assert_eq!((0, Some(10)), broadcast.size_hint());
+    assert_eq!(3, (&mut broadcast).take(3).count().await);
+    assert_eq!((0, Some(7)), broadcast.size_hint());
+    assert_eq!((3, Some(10)), slow.size_hint());
+    broadcast.close_after(5);
+    assert_eq!((0, Some(2)), broadcast.size_hint());
+}
+
+#[test]
+fn repeated_polls_keep_a_single_waker() {
+    struct Count(atomic::AtomicUsize);
+    impl futures::task::ArcWake for Count {
+        fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+            arc_self.0.fetch_add(1, atomic::Ordering::SeqCst);
+        }
+    }
+    let (sender, mut receiver) = stream_broadcast::channel::<i32>(3);
+    let wakes = std::sync::Arc::new(Count(atomic::AtomicUsize::new(0)));
+    let waker = futures::task::waker(wakes.clone());
+    let mut cx = std::task::Context::from_waker(&waker);
+    for _ in 0..3 {
+        assert!(receiver.poll_next_unpin(&mut cx).is_pending());
+    }
+    sender.push(1);
+    assert_eq!(1, wakes.0.load(atomic::Ordering::SeqCst));
+    assert_eq!(Some(Some((0, 1))), receiver.try_next_now());
+}
+
+#[tokio::test]
+async fn broadcast_arc_shares_the_cached_item() {
+    let broadcast = futures::stream::iter([String::from("a"), String::from("b")])
+        .fuse()
+        .broadcast_arc(1);
+    let slow = broadcast.clone();
+    let received = broadcast.collect::<Vec<_>>().await;
+    assert_eq!(2, received.len());
+    let lagging = slow.collect::<Vec<_>>().await;
+    assert_eq!(1, lagging[0].0);
+    assert!(std::sync::Arc::ptr_eq(&received[1].1, &lagging[0].1));
+}
+
+#[tokio::test]
+async fn broadcast_shared_accepts_items_without_clone() {
+    use stream_broadcast::SharedBroadcastExt;
+
+    struct Handle(std::sync::mpsc::Sender<()>);
+    let (tx, _rx) = std::sync::mpsc::channel();
+    let broadcast = futures::stream::iter([Handle(tx.clone()), Handle(tx)])
+        .fuse()
+        .broadcast_shared(1);
+    let slow = broadcast.clone();
+    assert_eq!(2, broadcast.count().await);
+    let lagging = slow.collect::<Vec<_>>().await;
+    assert_eq!(1, lagging.len());
+    assert_eq!(1, lagging[0].0);
+    assert!(lagging[0].1 .0.send(()).is_ok());
+}
+
+#[tokio::test]
+async fn next_batch_only_waits_for_the_first_item() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast(5);
+    {
+        let mut batch = std::pin::pin!(broadcast.next_batch(10));
+        assert!(futures::poll!(batch.as_mut()).is_pending());
+        tx.unbounded_send(1).unwrap();
+        tx.unbounded_send(2).unwrap();
+        assert_eq!(Some(vec![(0, 1)]), batch.await);
+    }
+    assert_eq!(Some(vec![(0, 2)]), broadcast.next_batch(10).await);
+}
+
+#[tokio::test]
+async fn unfused_streams_are_not_polled_after_termination() {
+    let mut terminated = false;
+    let source = futures::stream::poll_fn(move |_| {
+        assert!(!terminated, "polled after termination");
+        terminated = true;
+        std::task::Poll::<Option<i32>>::Ready(None)
+    });
+    let mut broadcast = source.broadcast(3);
+    let mut weak = broadcast.downgrade();
+    assert_eq!(None, broadcast.next().await);
+    assert!(broadcast.is_terminated());
+    assert_eq!(None, broadcast.clone().next().await);
+    assert_eq!(None, weak.next().await);
+}
+
+#[tokio::test]
+async fn try_broadcast_replays_shared_errors() {
+    use stream_broadcast::TryBroadcastExt;
+
+    let broadcast =
+        futures::stream::iter([Ok(1), Err(std::io::Error::other("boom")), Ok(2)]).try_broadcast(1);
+    let slow = broadcast.clone();
+    let fast = broadcast.collect::<Vec<_>>().await;
+    assert_eq!(3, fast.len());
+    let lagging = slow.collect::<Vec<_>>().await;
+    assert_eq!(1, lagging.len());
+    assert_eq!(2, lagging[0].0);
+    let Err(ref error) = fast[1].1 else {
+        panic!("expected an error");
+    };
+    assert_eq!("boom", error.to_string());
+}
+
+#[tokio::test]
+async fn metrics_count_produced_and_unseen_items() {
+    #[derive(Default)]
+    struct Counts {
+        produced: u64,
+        unseen: u64,
+        woken: usize,
+    }
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Counts>>);
+    impl stream_broadcast::BroadcastMetrics for Recorder {
+        fn item_produced(&mut self) {
+            self.0.lock().unwrap().produced += 1;
+        }
+        fn item_dropped_unseen(&mut self) {
+            self.0.lock().unwrap().unseen += 1;
+        }
+        fn subscribers_woken(&mut self, woken: usize) {
+            self.0.lock().unwrap().woken += woken;
+        }
+    }
+
+    let counts = std::sync::Arc::new(std::sync::Mutex::new(Counts::default()));
+    let mut broadcast = futures::stream::iter(0..5)
+        .broadcast_builder()
+        .capacity(2)
+        .metrics(Recorder(counts.clone()))
+        .build();
+    let _slow = broadcast.clone();
+    assert_eq!(5, (&mut broadcast).count().await);
+    let counts = counts.lock().unwrap();
+    assert_eq!((5, 3, 0), (counts.produced, counts.unseen, counts.woken));
+}
+
+#[tokio::test]
+async fn on_lag_reports_each_lagging_subscriber() {
+    let lags = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let lags2 = lags.clone();
+    let mut broadcast = futures::stream::iter(0..6)
+        .broadcast_builder()
+        .capacity(2)
+        .on_lag(move |id, skipped| lags2.lock().unwrap().push((id, skipped)))
+        .build();
+    let first = broadcast.clone();
+    let second = broadcast.clone();
+    let (first_id, second_id) = (first.subscriber_id(), second.subscriber_id());
+    assert_eq!(6, (&mut broadcast).count().await);
+    assert_eq!(2, first.count().await);
+    assert_eq!(2, second.count().await);
+    assert_eq!(vec![(first_id, 4), (second_id, 4)], *lags.lock().unwrap());
+}
+
+#[tokio::test]
+async fn panicking_stream_terminates_all_subscribers() {
+    let mut polls = 0;
+    let source = futures::stream::poll_fn(move |_| {
+        polls += 1;
+        assert!(polls < 2, "upstream failed");
+        std::task::Poll::Ready(Some(polls))
+    });
+    let mut broadcast = source.broadcast(3);
+    let lagging = broadcast.clone();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert_eq!(None, broadcast.next().await);
+    assert!(broadcast.is_terminated());
+    assert_eq!(vec![(0, 1)], lagging.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn poisoned_state_terminates_with_policy() {
+    struct PanicOnClone(bool);
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            assert!(!self.0, "clone failed");
+            PanicOnClone(self.0)
+        }
+    }
+
+    let mut broadcast = futures::stream::iter([PanicOnClone(false), PanicOnClone(true)])
+        .broadcast_builder()
+        .poison_policy(stream_broadcast::PoisonPolicy::Terminate)
+        .build();
+    let mut other = broadcast.clone();
+    assert!(broadcast.next().await.is_some());
+    let failed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        futures::executor::block_on(broadcast.next())
+    }));
+    assert!(failed.is_err());
+    assert!(other.next().await.is_none());
+}
+
+#[tokio::test]
+async fn local_broadcast_skips_like_the_shared_one() {
+    let mut broadcast = futures::stream::iter(0..5).broadcast_local(2);
+    let slow = broadcast.clone();
+    assert_eq!(5, (&mut broadcast).count().await);
+    assert_eq!(5, slow.lag());
+    assert_eq!(vec![(3, 3), (0, 4)], slow.collect::<Vec<_>>().await);
+    assert!(broadcast.is_terminated());
+}
+
+#[tokio::test]
+async fn paused_broadcast_does_not_poll_upstream() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast(3);
+    let mut paused = broadcast.clone();
+    paused.pause();
+    assert!(paused.is_paused());
+    tx.unbounded_send(1).unwrap();
+    assert!(futures::poll!(paused.next()).is_pending());
+    assert_eq!(0, broadcast.lag());
+    paused.resume();
+    assert!(!paused.is_paused());
+    assert_eq!(Some((0, 1)), paused.next().await);
+    assert_eq!(1, broadcast.lag());
+}
+
+#[tokio::test]
+async fn handles_of_pinned_streams_are_unpin() {
+    let source = futures::stream::unfold(0, |x| async move { Some((x, x + 1)) });
+    let mut broadcast = source.broadcast(3);
+    let mut weak = broadcast.downgrade();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(Some((0, 0)), weak.next().await);
+}
+
+#[tokio::test]
+async fn merge_waits_for_all_sources() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast =
+        StreamBroadcast::merge([futures::stream::iter(vec![1]).boxed(), rx.boxed()], 3);
+    let late = broadcast.clone();
+    assert_eq!(Some((0, (0, 1))), broadcast.next().await);
+    assert!(futures::poll!(broadcast.next()).is_pending());
+    tx.unbounded_send(2).unwrap();
+    drop(tx);
+    assert_eq!(vec![(0, (1, 2))], broadcast.collect::<Vec<_>>().await);
+    assert_eq!(
+        vec![(0, (0, 1)), (0, (1, 2))],
+        late.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn relay_reports_items_skipped_by_the_root() {
+    let mut root = futures::stream::iter(0..6).fuse().broadcast(2);
+    let relay = root.relay(8);
+    assert_eq!(6, (&mut root).count().await);
+    assert_eq!(vec![(4, 4), (0, 5)], relay.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn backlog_is_private_to_a_single_broadcast() {
+    let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let with_backlog = broadcast.clone();
+    let without_backlog = broadcast.clone();
+    with_backlog.set_backlog(8);
+    assert_eq!(5, (&mut broadcast).count().await);
+    assert_eq!(
+        vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+        with_backlog.collect::<Vec<_>>().await
+    );
+    assert_eq!(
+        vec![(3, 3), (0, 4)],
+        without_backlog.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn higher_priority_is_woken_first() {
+    struct Record(u8, std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+    impl futures::task::ArcWake for Record {
+        fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+            arc_self.1.lock().unwrap().push(arc_self.0);
+        }
+    }
+    let woken = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast_builder().priority(1).build();
+    let mut subscribers = (0..4).map(|_| broadcast.clone()).collect::<Vec<_>>();
+    subscribers[1].set_priority(5);
+    subscribers[3].set_priority(-1);
+    for (i, subscriber) in subscribers.iter_mut().enumerate() {
+        let waker = futures::task::waker(std::sync::Arc::new(Record(i as u8, woken.clone())));
+        let mut cx = std::task::Context::from_waker(&waker);
+        assert!(subscriber.poll_next_unpin(&mut cx).is_pending());
+    }
+    tx.unbounded_send(1).unwrap();
+    // The source wakes the last subscriber, which polled it
+    woken.lock().unwrap().clear();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    let woken = woken.lock().unwrap();
+    assert_eq!(4, woken.len());
+    assert_eq!([1, 3], [woken[0], woken[3]]);
+}
+
+#[test]
+fn rotate_driver_hands_off_polling_upstream() {
+    struct Flag(atomic::AtomicBool);
+    impl futures::task::ArcWake for Flag {
+        fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+            arc_self.0.store(true, atomic::Ordering::SeqCst);
+        }
+    }
+    let polls = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+    let counter = polls.clone();
+    let source = futures::stream::poll_fn(move |_| {
+        counter.fetch_add(1, atomic::Ordering::SeqCst);
+        std::task::Poll::<Option<u32>>::Pending
+    });
+    let mut a = source.broadcast_builder().rotate_driver().build();
+    let mut b = a.clone();
+    let flag = std::sync::Arc::new(Flag(atomic::AtomicBool::new(false)));
+    let waker_a = futures::task::waker(flag.clone());
+    let waker_b = futures::task::noop_waker();
+    let mut cx_a = std::task::Context::from_waker(&waker_a);
+    let mut cx_b = std::task::Context::from_waker(&waker_b);
+
+    assert!(a.poll_next_unpin(&mut cx_a).is_pending());
+    assert!(b.poll_next_unpin(&mut cx_b).is_pending());
+    assert_eq!(2, polls.load(atomic::Ordering::SeqCst));
+    assert!(b.poll_next_unpin(&mut cx_b).is_pending());
+    assert_eq!(2, polls.load(atomic::Ordering::SeqCst));
+    assert!(flag.0.load(atomic::Ordering::SeqCst));
+    assert!(a.poll_next_unpin(&mut cx_a).is_pending());
+    assert_eq!(3, polls.load(atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn keep_alive_handle_keeps_weak_broadcasts_running() {
+    let broadcast = futures::stream::iter(0..3).fuse().broadcast(3);
+    let mut weak = broadcast.downgrade();
+    let handle = broadcast.keep_alive();
+    drop(broadcast);
+    assert_eq!(Some((0, 0)), weak.next().await);
+    assert_eq!(0, handle.subscribe().lag());
+    drop(handle);
+    assert_eq!(None, weak.next().await);
+}
+
+#[tokio::test]
+async fn taps_see_every_item_once() {
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen_by_tap = seen.clone();
+    let broadcast = futures::stream::iter(0..4)
+        .fuse()
+        .broadcast_builder()
+        .capacity(1)
+        .inspect(|_| {})
+        .build();
+    broadcast.tap(move |x| seen_by_tap.lock().unwrap().push(*x));
+    let other = broadcast.clone();
+    assert_eq!(4, broadcast.count().await);
+    assert_eq!(vec![(3, 3)], other.collect::<Vec<_>>().await);
+    assert_eq!(vec![0, 1, 2, 3], *seen.lock().unwrap());
+}
+
+#[cfg(feature = "spill")]
+#[tokio::test]
+async fn spilled_items_are_read_before_the_cache() {
+    struct Codec;
+    impl stream_broadcast::SpillCodec<u32> for Codec {
+        fn encode(&mut self, item: &u32, buf: &mut Vec<u8>) {
+            buf.extend(item.to_le_bytes());
+        }
+        fn decode(&mut self, bytes: &[u8]) -> std::io::Result<u32> {
+            Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+        }
+    }
+    let path = std::env::temp_dir().join("stream-broadcast-spill-test");
+    let file = std::fs::File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path)
+        .unwrap();
+    let mut broadcast = futures::stream::iter(0..10u32)
+        .fuse()
+        .broadcast_builder()
+        .capacity(3)
+        .spill(file, Codec)
+        .build();
+    let mut lagging = broadcast.clone();
+    assert_eq!(6, (&mut broadcast).take(6).count().await);
+    assert_eq!(Some((0, 0)), lagging.next().await);
+    assert_eq!(4, (&mut broadcast).count().await);
+    assert_eq!(
+        (1..10).map(|x| (0, x)).collect::<Vec<_>>(),
+        lagging.collect::<Vec<_>>().await
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(all(feature = "serde", feature = "tokio"))]
+#[tokio::test]
+async fn restore_keeps_positions_and_gaps() {
+    let (tx, rx) = tokio::sync::broadcast::channel(2);
+    let mut broadcast = StreamBroadcast::from_tokio(rx, 4);
+    for i in 0..4 {
+        tx.send(i).unwrap();
+    }
+    drop(tx);
+    assert_eq!(
+        vec![(2, 2), (0, 3)],
+        (&mut broadcast).collect::<Vec<_>>().await
+    );
+    let snapshot = broadcast.snapshot();
+    assert_eq!(4, snapshot.global_pos);
+    assert_eq!(vec![None, None, Some(2), Some(3)], snapshot.items);
+
+    let restored = StreamBroadcast::restore(futures::stream::iter(4..5), 2, snapshot);
+    assert_eq!(2, restored.oldest_available_pos());
+    let resumed = restored.subscribe_at(3).ok().unwrap();
+    assert_eq!(vec![(0, 3), (0, 4)], resumed.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn custom_storage_skips_like_the_ring() {
+    #[derive(Default)]
+    struct Deque {
+        slots: std::collections::VecDeque<Option<u32>>,
+        first: u64,
+        capacity: usize,
+    }
+    impl stream_broadcast::BroadcastStorage<u32> for Deque {
+        fn capacity(&self) -> usize {
+            self.capacity
+        }
+        fn push(&mut self, pos: u64, slot: Option<u32>) {
+            if self.slots.is_empty() {
+                self.first = pos;
+            }
+            self.slots.push_back(slot);
+        }
+        fn get(&self, pos: u64) -> Option<&u32> {
+            let index = pos.checked_sub(self.first)?;
+            self.slots.get(index as usize)?.as_ref()
+        }
+        fn evict(&mut self, _pos: u64) -> Option<u32> {
+            self.first += 1;
+            self.slots.pop_front().flatten()
+        }
+        fn resize(&mut self, capacity: usize, _stored: std::ops::Range<u64>) {
+            self.capacity = capacity;
+        }
+    }
+    let mut broadcast = futures::stream::iter(0..6u32)
+        .fuse()
+        .broadcast_builder()
+        .storage(Deque::default())
+        .capacity(2)
+        .build();
+    let lagging = broadcast.clone();
+    assert_eq!(6, (&mut broadcast).count().await);
+    assert_eq!(vec![(4, 4), (0, 5)], lagging.collect::<Vec<_>>().await);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn inactivity_timeout_restarts_after_items() {
+    use stream_broadcast::TimeoutEvent;
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let broadcast = rx.broadcast(3);
+    let mut events = broadcast
+        .clone()
+        .inactivity_timeout(std::time::Duration::from_millis(50));
+    assert_eq!(Some(TimeoutEvent::Timeout), events.next().await);
+    assert_eq!(Some(TimeoutEvent::Timeout), events.next().await);
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(Some(TimeoutEvent::Item(0, 1)), events.next().await);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    tx.unbounded_send(2).unwrap();
+    assert_eq!(Some(TimeoutEvent::Item(0, 2)), events.next().await);
+    drop(tx);
+    assert_eq!(None, events.next().await);
+    assert!(events.is_terminated());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn heartbeats_reach_all_broadcasts() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx
+        .broadcast_builder()
+        .heartbeat(std::time::Duration::from_millis(30), || 0)
+        .build();
+    let mut other = broadcast.clone();
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(Some((0, 1)), other.next().await);
+    assert_eq!(Some((0, 0)), other.next().await);
+    assert_eq!(2, broadcast.buffered_len());
+}
+
+#[tokio::test]
+async fn throttled_broadcast_lags_instead_of_blocking() {
+    let interval = std::time::Duration::from_millis(20);
+    let mut broadcast = futures::stream::iter(0..6).fuse().broadcast(2);
+    let throttled = broadcast.clone().throttle(interval, tokio::time::sleep);
+    let start = std::time::Instant::now();
+    let (all, throttled) = futures::join!((&mut broadcast).count(), throttled.collect::<Vec<_>>());
+    assert_eq!(6, all);
+    assert_eq!(Some(&(0, 5)), throttled.last());
+    assert_eq!(
+        6,
+        throttled
+            .iter()
+            .map(|(skipped, _)| skipped + 1)
+            .sum::<u64>()
+    );
+    assert!(start.elapsed() >= interval * (throttled.len() as u32 - 1));
+}
+
+#[tokio::test]
+async fn dedup_keeps_offsets_truthful() {
+    let mut broadcast = futures::stream::iter([0, 0, 1, 1, 2, 3, 3, 4])
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .dedup()
+        .build();
+    let lagging = broadcast.clone();
+    assert_eq!(5, (&mut broadcast).count().await);
+    assert_eq!(vec![(3, 3), (0, 4)], lagging.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn distinct_only_filters_its_own_broadcast() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut distinct = rx.broadcast(4);
+    let mut all = distinct.clone();
+    distinct.distinct();
+    for x in [1, 1, 1] {
+        tx.unbounded_send(x).unwrap();
+    }
+    assert_eq!(Some((0, 1)), distinct.next().await);
+    assert!(futures::poll!(distinct.next()).is_pending());
+    tx.unbounded_send(2).unwrap();
+    drop(tx);
+    assert_eq!(vec![(0, 2)], distinct.collect::<Vec<_>>().await);
+    assert_eq!(4, (&mut all).count().await);
+
+    let mut distinct = futures::stream::iter([1, 1, 1, 2])
+        .fuse()
+        .broadcast_with_backpressure(1);
+    distinct.distinct();
+    assert_eq!(
+        vec![(0, 1), (0, 2)],
+        (&mut distinct).collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn late_stateful_clone_starts_with_current_state() {
+    use stream_broadcast::StateEvent;
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut first = rx.broadcast_with_state(2, Vec::new(), |all, x| all.push(*x));
+    assert_eq!(Some(StateEvent::Snapshot(vec![])), first.next().await);
+    for x in 0..3 {
+        tx.unbounded_send(x).unwrap();
+    }
+    assert_eq!(Some(StateEvent::Delta(0, 0)), first.next().await);
+    let late = first.clone();
+    drop(tx);
+    assert_eq!(vec![0], first.state());
+    assert_eq!(
+        vec![
+            StateEvent::Snapshot(vec![0]),
+            StateEvent::Delta(0, 1),
+            StateEvent::Delta(0, 2)
+        ],
+        late.collect::<Vec<_>>().await
+    );
+    assert_eq!(vec![0, 1, 2], first.state());
+}
+
+#[tokio::test]
+async fn seed_does_not_occupy_the_cache() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast_builder().capacity(1).seed(-1).build();
+    assert_eq!(Some((0, -1)), broadcast.next().await);
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    let mut late = broadcast.clone();
+    assert_eq!(Some((0, -1)), late.next().await);
+    assert_eq!(1, broadcast.buffered_len());
+    tx.unbounded_send(2).unwrap();
+    assert_eq!(Some((0, 2)), late.next().await);
+    assert_eq!(Some((0, 2)), broadcast.next().await);
+}
+
+#[tokio::test]
+async fn only_the_newest_checkpoints_are_kept() {
+    let mut broadcast = futures::stream::iter(0..12)
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .checkpoints(2, |_, x| x % 2 == 0)
+        .build();
+    let lagging = broadcast.clone();
+    assert_eq!(12, (&mut broadcast).count().await);
+    assert_eq!(
+        vec![(6, 6), (1, 8), (1, 10), (0, 11)],
+        lagging.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn backfill_reports_items_missing_in_the_store() {
+    struct Store;
+    impl stream_broadcast::Backfill<u64> for Store {
+        type Fetch = futures::future::BoxFuture<'static, Vec<u64>>;
+        fn fetch(&mut self, range: std::ops::Range<u64>) -> Self::Fetch {
+            Box::pin(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+                // The store lost the first item of every range
+                range.skip(1).collect()
+            })
+        }
+    }
+    let mut broadcast = futures::stream::iter(0..6u64).fuse().broadcast(2);
+    let mut lagging = broadcast.clone().with_backfill(Store);
+    assert_eq!(6, (&mut broadcast).count().await);
+    assert_eq!(
+        vec![(0, 1), (0, 2), (0, 3), (1, 4), (0, 5)],
+        (&mut lagging).collect::<Vec<_>>().await
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn backpressure_for_falls_back_to_overwriting() {
+    let duration = std::time::Duration::from_millis(30);
+    let mut broadcast = futures::stream::iter(0..5)
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .lag_policy(stream_broadcast::LagPolicy::BackpressureFor(duration))
+        .build();
+    let mut stalled = broadcast.clone();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert!(futures::poll!(broadcast.next()).is_pending());
+    assert_eq!(Some((0, 0)), stalled.next().await);
+    assert_eq!(Some((0, 2)), broadcast.next().await);
+    let start = std::time::Instant::now();
+    assert_eq!(2, (&mut broadcast).count().await);
+    assert!(start.elapsed() >= duration);
+    assert_eq!(vec![(2, 3), (0, 4)], stalled.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn forward_all_reports_errors_per_sink() {
+    let broadcast = futures::stream::iter(0..4)
+        .fuse()
+        .broadcast_with_backpressure(1);
+    let (closed, rx) = futures::channel::mpsc::channel(0);
+    drop(rx);
+    let (open, rx) = futures::channel::mpsc::channel(4);
+    let forwarded = broadcast.forward_all([closed, open], 0);
+    drop(broadcast);
+    let results = forwarded.await;
+    assert!(results[0].as_ref().unwrap_err().is_disconnected());
+    assert!(results[1].is_ok());
+    assert_eq!(4, rx.count().await);
+}
+
+#[tokio::test]
+async fn added_source_wakes_waiting_broadcasts() {
+    let (tx, rx) = futures::channel::mpsc::unbounded::<u32>();
+    let broadcast = StreamBroadcast::merge([rx.boxed()], 4);
+    let mut waiting = broadcast.clone();
+    assert!(futures::poll!(waiting.next()).is_pending());
+    let (added_tx, added_rx) = futures::channel::mpsc::unbounded();
+    assert_eq!(1, broadcast.add_source(added_rx.boxed()));
+    added_tx.unbounded_send(7).unwrap();
+    assert_eq!(Some((0, (1, 7))), waiting.next().await);
+    tx.unbounded_send(8).unwrap();
+    assert_eq!(Some((0, (0, 8))), waiting.next().await);
+    assert_eq!(2, broadcast.lag());
+}
+
+#[tokio::test]
+async fn dropped_event_receivers_are_forgotten() {
+    use stream_broadcast::SubscriberEvent;
+
+    let broadcast = futures::stream::iter(0..3).fuse().broadcast(2);
+    drop(broadcast.events());
+    let mut events = broadcast.events();
+    let clone = broadcast.clone();
+    let id = clone.subscriber_id();
+    assert_eq!(Some(SubscriberEvent::Joined(id)), events.next().await);
+    drop(clone);
+    assert_eq!(Some(SubscriberEvent::Dropped(id)), events.next().await);
+    assert!(futures::poll!(events.next()).is_pending());
+}
+
+#[tokio::test]
+async fn min_subscribers_holds_back_destructive_source() {
+    let polls = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+    let counter = polls.clone();
+    let source = futures::stream::iter(0..3).inspect(move |_| {
+        counter.fetch_add(1, atomic::Ordering::SeqCst);
+    });
+    let mut broadcast = source.fuse().broadcast_builder().min_subscribers(2).build();
+    assert!(futures::poll!(broadcast.next()).is_pending());
+    assert_eq!(0, polls.load(atomic::Ordering::SeqCst));
+    let late = broadcast.clone();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    drop(late);
+    assert_eq!(2, broadcast.count().await);
+    assert_eq!(3, polls.load(atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn prefetch_follows_fastest_subscriber() {
+    let mut fast = futures::stream::iter(0..10)
+        .fuse()
+        .broadcast_builder()
+        .capacity(3)
+        .prefetch()
+        .build();
+    let slow = fast.clone();
+    let mut pump = pin!(fast.pump());
+    assert!(futures::poll!(&mut pump).is_pending());
+    assert_eq!(3, fast.lag());
+    assert_eq!(Some((0, 0)), fast.next().await);
+    assert!(futures::poll!(&mut pump).is_pending());
+    assert_eq!(3, fast.lag());
+    assert_eq!(4, slow.lag());
+    drop(slow);
+    assert_eq!(((), 9), futures::join!(pump, fast.count()));
+}
+
+#[cfg(feature = "tokio-util")]
+#[tokio::test]
+async fn cancellation_without_drain_drops_buffered_items() {
+    let token = tokio_util::sync::CancellationToken::new();
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx
+        .broadcast_builder()
+        .cancellation(token.clone(), false)
+        .build();
+    let lagging = broadcast.clone();
+    tx.unbounded_send(1).unwrap();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    let waiting = tokio::spawn(async move { broadcast.next().await });
+    tokio::task::yield_now().await;
+    token.cancel();
+    assert_eq!(None, waiting.await.unwrap());
+    assert!(lagging.is_terminated());
+    assert_eq!(0, lagging.count().await);
+}
+
+#[tokio::test]
+async fn skipped_total_counts_through_wrappers() {
+    let mut broadcast = futures::stream::iter(0..6).fuse().broadcast(2);
+    let mut slow = broadcast.clone().fallible();
+    (&mut broadcast).take(4).count().await;
+    assert!(matches!(slow.next().await, Some(Err(_))));
+    assert_eq!(Some(Ok(2)), slow.next().await);
+    let slow = slow.into_inner();
+    assert_eq!(2, slow.skipped_total());
+    assert_eq!(0, broadcast.skipped_total());
+}
+
+#[tokio::test]
+async fn stats_report_handles_and_positions() {
+    let mut broadcast = futures::stream::iter(0..5).fuse().broadcast(2);
+    let weak = broadcast.downgrade();
+    let lagging = broadcast.clone();
+    (&mut broadcast).take(3).count().await;
+    let stats = broadcast.stats();
+    assert_eq!((2, 1), (stats.strong_count, stats.weak_count));
+    assert_eq!(Some(0), stats.min_subscriber_pos);
+    assert_eq!(Some(3), stats.max_subscriber_pos);
+    assert_eq!(3, stats.items_produced);
+    assert_eq!(1, stats.items_dropped_unseen);
+    drop((weak, lagging));
+    let stats = broadcast.stats();
+    assert_eq!(Some(3), stats.min_subscriber_pos);
+    assert_eq!((1, 0), (stats.strong_count, stats.weak_count));
+}
+
+#[tokio::test]
+async fn clone_unlocked_panics_without_poisoning() {
+    static PANICKED: atomic::AtomicBool = atomic::AtomicBool::new(false);
+    struct PanicOnce(u32);
+    impl Clone for PanicOnce {
+        fn clone(&self) -> Self {
+            assert!(
+                PANICKED.swap(true, atomic::Ordering::SeqCst),
+                "clone failed"
+            );
+            PanicOnce(self.0)
+        }
+    }
+
+    let mut broadcast = futures::stream::iter([PanicOnce(1), PanicOnce(2)])
+        .broadcast_builder()
+        .clone_unlocked()
+        .build();
+    let mut other = broadcast.clone();
+    let failed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        futures::executor::block_on(broadcast.next())
+    }));
+    assert!(failed.is_err());
+    assert!(matches!(other.next().await, Some((0, PanicOnce(1)))));
+    assert!(matches!(broadcast.next().await, Some((0, PanicOnce(2)))));
+}
+
+#[tokio::test]
+async fn sole_subscriber_receives_items_without_clone() {
+    static CLONES: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+    #[derive(Debug, PartialEq)]
+    struct Counted(u32);
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            CLONES.fetch_add(1, atomic::Ordering::SeqCst);
+            Counted(self.0)
+        }
+    }
+
+    let mut broadcast = futures::stream::iter((0..4).map(Counted))
+        .fuse()
+        .broadcast_builder()
+        .move_to_sole_subscriber()
+        .build();
+    assert_eq!(Some((0, Counted(0))), broadcast.next().await);
+    assert_eq!(0, CLONES.load(atomic::Ordering::SeqCst));
+    let mut clone = broadcast.clone();
+    assert_eq!(Some((0, Counted(1))), broadcast.next().await);
+    assert_eq!(Some((0, Counted(1))), clone.next().await);
+    assert_eq!(2, CLONES.load(atomic::Ordering::SeqCst));
+    drop(clone);
+    assert_eq!(2, broadcast.count().await);
+    assert_eq!(2, CLONES.load(atomic::Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn on_evict_skips_items_for_overflow_sink() {
+    let (evicted_tx, evicted_rx) = futures::channel::mpsc::unbounded();
+    let (overflow_tx, overflow_rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = futures::stream::iter(0..5)
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .on_evict(move |x| evicted_tx.unbounded_send(x).unwrap())
+        .overflow_sink(move |x| overflow_tx.unbounded_send(x).unwrap())
+        .build();
+    let lagging = broadcast.clone();
+    assert_eq!(3, (&mut broadcast).take(3).count().await);
+    drop(lagging);
+    assert_eq!(2, broadcast.count().await);
+    assert_eq!(vec![0], overflow_rx.collect::<Vec<_>>().await);
+    assert_eq!(vec![1, 2], evicted_rx.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn peek_leaves_the_next_item_in_place() {
+    use stream_broadcast::ErrorDelivery;
+
+    let mut fast = futures::stream::iter([Ok(0), Ok(1), Ok(2), Err("failed"), Ok(3)])
+        .fuse()
+        .broadcast_builder()
+        .capacity(2)
+        .error_delivery(ErrorDelivery::Poller)
+        .build();
+    let mut slow = fast.clone();
+    assert_eq!(Some((0, Ok(0))), fast.peek().await);
+    assert_eq!(Some((0, Ok(0))), slow.peek().await);
+    assert_eq!(3, (&mut fast).take(3).count().await);
+    assert_eq!(Some((1, Ok(1))), slow.peek().await);
+    assert_eq!(3, slow.lag());
+    assert_eq!(Some((1, Ok(1))), slow.next().await);
+
+    assert_eq!(Some((0, Err("failed"))), fast.peek().await);
+    assert_eq!(Some((0, Ok(2))), slow.next().await);
+    assert_eq!(Some((0, Err("failed"))), fast.next().await);
+    assert_eq!(vec![(0, Ok(3))], slow.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn buffer_snapshot_copies_without_consuming() {
+    let mut broadcast = futures::stream::iter(["a", "b", "c"]).fuse().broadcast(2);
+    let slow = broadcast.clone();
+    assert!(broadcast.buffer_snapshot().is_empty());
+    assert_eq!(Some((0, "a")), broadcast.next().await);
+    assert_eq!(vec![(0, "a")], slow.buffer_snapshot());
+    assert_eq!(2, (&mut broadcast).count().await);
+    assert_eq!(vec![(1, "b"), (2, "c")], slow.buffer_snapshot());
+    assert_eq!(3, slow.lag());
+    assert_eq!(vec![(1, "b"), (0, "c")], slow.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn next_if_lets_distributed_workers_pick_their_items() {
+    use stream_broadcast::Delivery;
+
+    let mut evens = futures::stream::iter(0..6)
+        .fuse()
+        .broadcast_builder()
+        .delivery(Delivery::Distribute)
+        .build();
+    let mut odds = evens.clone();
+    let mut received = (Vec::new(), Vec::new());
+    loop {
+        if let Some((_, x)) = evens.next_if(|x| x % 2 == 0).await {
+            received.0.push(x);
+        } else if let Some((_, x)) = odds.next_if(|x| x % 2 == 1).await {
+            received.1.push(x);
+        } else {
+            break;
+        }
+    }
+    assert_eq!((vec![0, 2, 4], vec![1, 3, 5]), received);
+}
+
+#[tokio::test]
+async fn swap_source_wakes_the_waiting_broadcasts() {
+    let broadcast = futures::stream::pending::<u32>()
+        .boxed()
+        .fuse()
+        .broadcast(4);
+    let handle = broadcast.keep_alive();
+    let waiting = tokio::spawn(broadcast.collect::<Vec<_>>());
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    handle.swap_source(futures::stream::iter([1, 2]).boxed().fuse());
+    assert_eq!(vec![(0, 1), (0, 2)], waiting.await.unwrap());
+}
+
+#[tokio::test]
+async fn relay_reports_per_item_offsets_of_a_cumulative_root() {
+    use stream_broadcast::OffsetMode;
+
+    let mut root = futures::stream::iter(0..6).fuse().broadcast(2);
+    root.set_offset_mode(OffsetMode::Cumulative);
+    let relay = root.relay(8);
+    assert_eq!(6, (&mut root).count().await);
+    assert_eq!(vec![(4, 4), (0, 5)], relay.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn adapters_read_offsets_per_item_of_cumulative_broadcasts() {
+    use stream_broadcast::{BroadcastError, OffsetMode};
+
+    struct Store;
+    impl stream_broadcast::Backfill<u64> for Store {
+        type Fetch = futures::future::Ready<Vec<u64>>;
+        fn fetch(&mut self, range: std::ops::Range<u64>) -> Self::Fetch {
+            futures::future::ready(range.collect())
+        }
+    }
+    let mut broadcast = futures::stream::iter(0..6u64).fuse().broadcast(2);
+    let lagging = |broadcast: &stream_broadcast::StreamBroadcast<_>| {
+        let mut lagging = broadcast.clone();
+        lagging.set_offset_mode(OffsetMode::Cumulative);
+        lagging
+    };
+    let (fallible, items, backfill) = (
+        lagging(&broadcast).fallible(),
+        lagging(&broadcast).items(),
+        lagging(&broadcast).with_backfill(Store),
+    );
+    assert_eq!(6, (&mut broadcast).count().await);
+
+    assert_eq!(
+        vec![Err(BroadcastError::Lagged { skipped: 4 }), Ok(4), Ok(5)],
+        fallible.collect::<Vec<_>>().await
+    );
+    let mut items = items;
+    assert_eq!(vec![4, 5], (&mut items).collect::<Vec<_>>().await);
+    assert_eq!(4, items.skipped());
+    assert_eq!(
+        (0..6).map(|x| (0, x)).collect::<Vec<_>>(),
+        backfill.collect::<Vec<_>>().await
+    );
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn bytes_broadcast_reads_offsets_per_item_of_cumulative_broadcasts() {
+    use bytes::Bytes;
+    use stream_broadcast::{BytesBroadcast, OffsetMode};
+
+    let chunks = (0..4u8).map(|i| Bytes::from(vec![i; 2]));
+    let mut broadcast = futures::stream::iter(chunks).fuse().broadcast(4);
+    let mut clone = broadcast.clone();
+    clone.set_offset_mode(OffsetMode::Cumulative);
+    assert_eq!(4, (&mut broadcast).count().await);
+
+    let mut written = Vec::new();
+    let bytes = BytesBroadcast::from(clone);
+    assert_eq!(8, bytes.copy_to(&mut written).await.unwrap());
+}
+
+#[tokio::test]
+async fn events_of_cumulative_broadcasts_report_lags_once() {
+    use stream_broadcast::{BroadcastEvent, OffsetMode};
+
+    let mut broadcast = futures::stream::iter(0..6).fuse().broadcast(2);
+    let mut lagging = broadcast.clone();
+    lagging.set_offset_mode(OffsetMode::Cumulative);
+    let events = lagging.into_events();
+    assert_eq!(6, (&mut broadcast).count().await);
+    assert_eq!(
+        vec![
+            BroadcastEvent::Lagged(4),
+            BroadcastEvent::Item(4),
+            BroadcastEvent::Item(5),
+            BroadcastEvent::Closed
+        ],
+        events.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn panicking_stream_terminates_all_key_subscriptions() {
+    let mut polls = 0;
+    let source = futures::stream::poll_fn(move |_| {
+        polls += 1;
+        assert!(polls < 3, "upstream failed");
+        std::task::Poll::Ready(Some(polls))
+    });
+    let keyed = source.broadcast_by_key(3, |x| x % 2);
+    let mut odds = keyed.subscribe_key(1);
+    let evens = keyed.subscribe_key(0);
+    assert_eq!(Some((0, 1)), odds.next().await);
+    assert_eq!(None, odds.next().await);
+    assert!(odds.is_terminated());
+    assert_eq!(vec![(0, 2)], evens.collect::<Vec<_>>().await);
+}
+
+#[tokio::test]
+async fn poisoned_keyed_state_terminates_with_policy() {
+    let keyed = futures::stream::iter(0..4)
+        .fuse()
+        .broadcast_by_key(3, |x| {
+            assert!(*x < 2, "key failed");
+            x % 2
+        })
+        .poison_policy(stream_broadcast::PoisonPolicy::Terminate);
+    let mut evens = keyed.subscribe_key(0);
+    let mut odds = keyed.subscribe_key(1);
+    assert_eq!(Some((0, 0)), evens.next().await);
+    let failed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        futures::executor::block_on(evens.next())
+    }));
+    assert!(failed.is_err());
+    assert_eq!(None, odds.next().await);
+    assert_eq!(None, evens.next().await);
+}
+
+#[tokio::test]
+async fn local_broadcast_supports_close_after_try_next_now_and_close() {
+    let mut broadcast = futures::stream::iter(0..5).fuse().broadcast_local(5);
+    let mut limited = broadcast.clone();
+    limited.close_after(2);
+    assert_eq!(None, broadcast.try_next_now());
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert_eq!(Some(Some((0, 0))), limited.try_next_now());
+    assert_eq!(Some((0, 1)), limited.next().await);
+    assert_eq!(Some(None), limited.try_next_now());
+    assert!(limited.is_terminated());
+    assert_eq!(2, broadcast.stats().items_produced);
+    broadcast.close();
+    assert_eq!(Some((0, 1)), broadcast.next().await);
+    assert_eq!(None, broadcast.next().await);
+}
+
+#[tokio::test]
+async fn channel_ignores_items_pushed_after_close() {
+    let (sender, receiver) = stream_broadcast::channel(3);
+    let sender2 = sender.clone();
+    let stats = receiver.clone();
+    sender.push(1);
+    sender.close();
+    sender2.push(2);
+    assert_eq!(vec![(0, 1)], receiver.collect::<Vec<_>>().await);
+    sender2.push(3);
+    assert_eq!(1, stats.stats().items_produced);
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn coalesced_bytes_with_frame_size_zero_pass_on_every_chunk() {
+    use stream_broadcast::BytesBroadcastExt;
+
+    let chunks = (0..3).map(|_| bytes::Bytes::from_static(b"ab"));
+    let broadcast = futures::stream::iter(chunks).broadcast_bytes_coalesced(64, 0);
+    let frames = broadcast.collect::<Vec<_>>().await;
+    assert_eq!(
+        vec![2, 2, 2],
+        frames.iter().map(|f| f.len()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn dropped_wait_for_subscribers_forgets_its_waker() {
+    struct Flag(atomic::AtomicBool);
+    impl futures::task::ArcWake for Flag {
+        fn wake_by_ref(arc_self: &std::sync::Arc<Self>) {
+            arc_self.0.store(true, atomic::Ordering::SeqCst);
+        }
+    }
+    let broadcast = futures::stream::iter(0..3).fuse().broadcast(3);
+    let flag = std::sync::Arc::new(Flag(atomic::AtomicBool::new(false)));
+    let waker = futures::task::waker(flag.clone());
+    let mut cx = std::task::Context::from_waker(&waker);
+    {
+        let mut waiting = pin!(broadcast.wait_for_subscribers(2));
+        assert!(std::future::Future::poll(waiting.as_mut(), &mut cx).is_pending());
+    }
+    let _joined = broadcast.clone();
+    assert!(!flag.0.load(atomic::Ordering::SeqCst));
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn restore_ignores_items_before_position_zero() {
+    let snapshot = stream_broadcast::BroadcastSnapshot {
+        global_pos: 2,
+        items: vec![Some(0), Some(1), Some(2)],
+    };
+    let restored = StreamBroadcast::restore(futures::stream::iter(3..4), 3, snapshot);
+    assert_eq!(0, restored.oldest_available_pos());
+    let resumed = restored.subscribe_at(0).ok().unwrap();
+    assert_eq!(
+        vec![(0, 1), (0, 2), (0, 3)],
+        resumed.collect::<Vec<_>>().await
+    );
+}
+
+#[tokio::test]
+async fn rotate_driver_takes_back_unclaimed_hand_offs() {
+    use futures::FutureExt;
+
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut a = rx.broadcast_builder().rotate_driver().build();
+    let mut b = a.clone();
+    // `b` is chosen as the next driver, but never polls again
+    assert!(b.next().now_or_never().is_none());
+    let received = async {
+        for i in 0..3 {
+            let mut next = a.next();
+            assert!((&mut next).now_or_never().is_none());
+            tx.unbounded_send(i).unwrap();
+            assert_eq!(Some((0, i)), next.await);
+        }
+    };
+    tokio::select! {
+        biased;
+        _ = tokio::time::sleep(std::time::Duration::from_secs(1)) => panic!("the broadcast stalled"),
+        _ = received => {}
+    }
+}
+
+#[tokio::test]
+async fn dropped_pump_lets_subscribers_poll_again() {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = rx.broadcast(3);
+    drop(broadcast.pump());
+    tx.unbounded_send(0).unwrap();
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+
+    let mut pump = Box::pin(broadcast.pump());
+    assert!(futures::FutureExt::now_or_never(pump.as_mut()).is_none());
+    let next = tokio::spawn(async move { broadcast.next().await });
+    tokio::task::yield_now().await;
+    drop(pump);
+    tx.unbounded_send(1).unwrap();
+    let next = tokio::time::timeout(std::time::Duration::from_secs(1), next).await;
+    assert_eq!(Some((0, 1)), next.unwrap().unwrap());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn driven_broadcast_outlives_its_task() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let mut broadcast = runtime.block_on(async {
+        let broadcast = rx.broadcast_driven(3);
+        tokio::task::yield_now().await;
+        broadcast
+    });
+    drop(runtime);
+    tx.unbounded_send(0).unwrap();
+    assert_eq!(Some((0, 0)), futures::executor::block_on(broadcast.next()));
+}
+
+#[cfg(feature = "bytes")]
+#[tokio::test]
+async fn broadcast_read_with_chunk_size_zero_reads_everything() {
+    use futures::AsyncReadExt;
+    use stream_broadcast::AsyncReadBroadcastExt;
+
+    let mut reader = futures::io::Cursor::new(b"hello").broadcast_read(0, 4);
+    let mut read = Vec::new();
+    assert_eq!(5, reader.read_to_end(&mut read).await.unwrap());
+    assert_eq!(b"hello", &read[..]);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn inactivity_timeout_starts_on_the_first_poll() {
+    use stream_broadcast::TimeoutEvent;
+    let (_tx, rx) = futures::channel::mpsc::unbounded::<i32>();
+    let duration = std::time::Duration::from_millis(50);
+    let mut events = rx.broadcast(3).inactivity_timeout(duration);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        tokio::time::sleep(duration).await;
+        let start = std::time::Instant::now();
+        assert_eq!(Some(TimeoutEvent::Timeout), events.next().await);
+        assert!(start.elapsed() >= duration);
+    });
+}
+
+#[tokio::test]
+async fn max_lag_observed_includes_caught_up_and_dropped_subscribers() {
+    let mut broadcast = futures::stream::iter(0..10).fuse().broadcast(3);
+    let mut caught_up = broadcast.clone();
+    let dropped = broadcast.clone();
+    for _ in 0..4 {
+        broadcast.next().await;
+    }
+    assert_eq!(Some((1, 1)), caught_up.next().await);
+    drop(dropped);
+    assert_eq!(4, broadcast.stats().max_lag_observed);
+    assert_eq!(6, (&mut broadcast).count().await);
+    assert_eq!(8, broadcast.stats().max_lag_observed);
+}
+
+#[tokio::test]
+async fn round_robin_skips_weak_and_paused_broadcasts() {
+    let a = futures::stream::iter(0..6).fuse().round_robin(6);
+    let b = a.clone();
+    let paused = a.clone();
+    paused.pause();
+    let _weak = a.downgrade();
+    let (a, b) = futures::join!(a.collect::<Vec<_>>(), b.collect::<Vec<_>>());
+    assert_eq!(6, a.len() + b.len());
+    assert_eq!(3, a.len());
+}
+
+#[tokio::test]
+async fn local_broadcast_dropped_while_polling_unsubscribes() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let dropped: Rc<RefCell<Option<Box<dyn std::any::Any>>>> = Default::default();
+    let drop_in_poll = dropped.clone();
+    let mut broadcast = futures::stream::iter(0..3)
+        .inspect(move |_| drop(drop_in_poll.borrow_mut().take()))
+        .broadcast_local(1);
+    *dropped.borrow_mut() = Some(Box::new(broadcast.clone()));
+    assert_eq!(2, broadcast.stats().strong_count);
+    assert_eq!(Some((0, 0)), broadcast.next().await);
+    assert!(dropped.borrow().is_none());
+    assert_eq!(1, broadcast.stats().strong_count);
+}
+
+#[test]
+#[should_panic(expected = "max must be greater than 0")]
+fn next_batch_rejects_empty_batches() {
+    let mut broadcast = futures::stream::iter(0..3).fuse().broadcast(3);
+    drop(broadcast.next_batch(0));
+}